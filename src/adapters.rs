@@ -1,14 +1,23 @@
 use clap::ValueEnum;
 
-use crate::utils::result::AppResult;
-use std::path::PathBuf;
+use crate::utils::result::{AppError, AppResult};
+use std::path::{Path, PathBuf};
 
 pub trait PartReaderPort: Send + Sync {
     fn get_value(&self, identifier: &str) -> AppResult<serde_yaml::Value>;
 
     fn get_filepathes_from_glob(&self, glob: &str) -> AppResult<Vec<String>>;
+
+    /// Raw content of a non part file, `identifier` includes its extension.
+    fn get_resource(&self, identifier: &str) -> AppResult<String> {
+        Err(AppError::FileSystem(format!(
+            "{identifier}: resources are not supported by this reader"
+        )))
+    }
 }
 
+// Ports take `&PathBuf` as external implementors already do.
+#[allow(clippy::ptr_arg)]
 pub trait SchemaReaderPort: Send + Sync {
     fn get_validation_schema(&self, identifier: &str) -> AppResult<serde_json::Value>;
 
@@ -43,6 +52,7 @@ impl ValueEnum for AssemblyOutputFormat {
     }
 }
 
+#[allow(clippy::ptr_arg)]
 pub trait AssemblyOutputPort: Send + Sync {
     fn output(
         &self,
@@ -52,6 +62,123 @@ pub trait AssemblyOutputPort: Send + Sync {
     ) -> AppResult<()>;
 }
 
+#[allow(clippy::ptr_arg)]
 pub trait SchemaOutputPort: Send + Sync {
     fn output(&self, value: &serde_json::Value, schema_path: &PathBuf) -> AppResult<()>;
 }
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TransformTrace {
+    pub formula: String,
+    pub key: String,
+    pub old_value: Option<serde_yaml::Value>,
+    pub new_value: serde_yaml::Value,
+    pub part: Option<String>,
+}
+
+pub trait TraceOutputPort: Send + Sync {
+    fn output(&self, traces: &[TransformTrace], file_path: &Path) -> AppResult<()>;
+}
+
+/// What an entry was assembled from, with a hash of each input, and what it gave.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BuildRecord {
+    pub inputs: std::collections::BTreeMap<String, String>,
+    pub output: serde_yaml::Value,
+    pub traces: Vec<TransformTrace>,
+    #[serde(default)]
+    pub warnings: Vec<String>,
+}
+
+pub trait BuildCachePort: Send + Sync {
+    fn get(&self, entry: &str) -> AppResult<Option<BuildRecord>>;
+
+    fn set(&self, entry: &str, record: &BuildRecord) -> AppResult<()>;
+}
+
+/// Parts, resources and glob patterns an entry was assembled from.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Dependencies {
+    pub parts: std::collections::BTreeSet<String>,
+    pub resources: std::collections::BTreeSet<String>,
+    pub globs: std::collections::BTreeSet<String>,
+}
+impl Dependencies {
+    /// Whether creating, editing or deleting `identifier`, a part identifier or a resource path,
+    /// can change the entry.
+    pub fn is_affected_by(&self, identifier: &str) -> bool {
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..glob::MatchOptions::new()
+        };
+
+        self.parts.contains(identifier)
+            || self.resources.contains(identifier)
+            || self.globs.iter().any(|glob| {
+                glob::Pattern::new(glob)
+                    .map(|pattern| pattern.matches_with(identifier, options))
+                    .unwrap_or(false)
+            })
+    }
+}
+
+/// An assembled entry, validated against `schema` when one was given.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assembly {
+    pub value: serde_yaml::Value,
+    pub schema: Option<serde_json::Value>,
+    pub warnings: Vec<String>,
+    pub metadata: AssemblyMetadata,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssemblyMetadata {
+    pub entry: String,
+    pub schema_id: Option<String>,
+    pub dependencies: Dependencies,
+    /// Assignments done by `_transform` formulas, only recorded with a trace output.
+    pub traces: Vec<TransformTrace>,
+    /// Whether the value comes from the build cache.
+    pub cached: bool,
+}
+
+/// Steps of an assembly, in order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PipelineStep {
+    /// Loading the entry with the parts it includes, a stage gets the entry as read before it.
+    Aggregate,
+    Mixins,
+    Transform,
+    Validate,
+    /// Handing the value to the outputs, what a stage gives after it is not used.
+    Output,
+}
+
+pub struct StageContext<'a> {
+    pub entry: &'a str,
+    pub variables: &'a std::collections::HashMap<String, String>,
+    pub step: PipelineStep,
+}
+
+/// User defined processing of the value around each step, like normalizing strings or
+/// sorting lists. Both hooks give back the value as is by default.
+pub trait PipelineStagePort: Send + Sync {
+    /// Names the stage and its settings, cached builds are reused only with the same stages.
+    fn id(&self) -> String;
+
+    fn before(
+        &self,
+        _context: &StageContext,
+        value: serde_yaml::Value,
+    ) -> AppResult<serde_yaml::Value> {
+        Ok(value)
+    }
+
+    fn after(
+        &self,
+        _context: &StageContext,
+        value: serde_yaml::Value,
+    ) -> AppResult<serde_yaml::Value> {
+        Ok(value)
+    }
+}
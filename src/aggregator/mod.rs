@@ -1,6 +1,13 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
-use crate::{adapters, mixins::MixIns, utils::result::AppResult, variables::Variables};
+use crate::{
+    adapters,
+    graph::{IncludeEdge, IncludeGraph, MixinEdge},
+    mixins::MixIns,
+    utils::result::{AppError, AppResult},
+    variables::Variables,
+};
+use regex::Regex;
 use serde_yaml::{
     value::{Tag, TaggedValue},
     Mapping, Value,
@@ -8,23 +15,109 @@ use serde_yaml::{
 
 pub struct YmlAggregator {
     reader: Arc<dyn adapters::PartReaderPort>,
-    pub mixins: MixIns,
+    pub(crate) mixins: MixIns,
+    /// Parts declaring each `_transform` formula, in declaration order, keyed by formula.
+    pub(crate) transform_origins: HashMap<String, Vec<String>>,
+    /// Includes and mixins met while loading.
+    pub(crate) graph: IncludeGraph,
+    /// Part each mixin value was declared in, in the order of `mixins` values.
+    pub(crate) mixin_parts: HashMap<String, Vec<String>>,
+    /// Dotted path of the assembled value each included part was written at.
+    pub(crate) part_paths: Vec<(String, String)>,
+    /// Things that did not stop the assembly but may be mistakes, like a missing optional part.
+    pub(crate) warnings: Vec<String>,
+    parts: Vec<String>,
+    /// Variables each part of `parts` is loaded with.
+    part_variables: Vec<Variables>,
+    path: Vec<String>,
 }
 
 impl YmlAggregator {
-    const INCLUDE_TAG_PREFIX: &'static str = "!inc::";
+    pub(crate) const INCLUDE_TAG_PREFIX: &'static str = "!inc::";
+    const INCLUDE_MAP_TAG_PREFIX: &'static str = "!inc-map::";
+    /// `!inc?::part` gives nothing instead of failing when the part does not exist.
+    const INCLUDE_OPTIONAL_TAG_PREFIX: &'static str = "!inc?::";
+    pub(crate) const EACH_TAG: &'static str = "!each";
+    const TRANSFORM_KEY: &'static str = "_transform";
+    /// `!inc::part#a.0.b` only keeps the node at `a.0.b` of the included part.
+    /// `#` is not allowed in a yml tag and must be written `%23`.
+    const SELECTOR_SEPARATOR: char = '#';
+    /// Same as the selector separator, given in the include variables.
+    const SELECT_KEY: &'static str = "_select";
 
     pub fn new(reader: Arc<dyn adapters::PartReaderPort>) -> Self {
         YmlAggregator {
             reader,
             mixins: MixIns::new(),
+            transform_origins: HashMap::new(),
+            graph: IncludeGraph::new(),
+            mixin_parts: HashMap::new(),
+            part_paths: vec![],
+            warnings: vec![],
+            parts: vec![],
+            part_variables: vec![],
+            path: vec![],
         }
     }
 
+    /// A part may include itself, behind a condition ending the recursion, as long as
+    /// its variables change on each include.
     pub fn load(&mut self, identifier: &str, variables: &Variables) -> AppResult<Value> {
+        let is_cycle = self
+            .parts
+            .iter()
+            .zip(self.part_variables.iter())
+            .any(|(part, part_variables)| part == identifier && part_variables == variables);
+        if is_cycle || self.parts.len() >= MAX_INCLUDE_DEPTH {
+            let mut cycle = self.parts.clone();
+            cycle.push(identifier.to_string());
+            return Err(AppError::IncludeCycle(cycle.join(" -> ")));
+        }
         let yml = self.reader.get_value(identifier)?;
-        let (yml, mixins) = parse_yml_part(yml, &variables)?;
+        self.load_value(identifier, yml, variables)
+    }
+
+    /// Loads `yml` as the content of the part `identifier`, as read or changed by a stage.
+    pub(crate) fn load_value(
+        &mut self,
+        identifier: &str,
+        yml: Value,
+        variables: &Variables,
+    ) -> AppResult<Value> {
+        self.parts.push(identifier.to_string());
+        self.part_variables.push(variables.clone());
+        let yml = self.assemble(yml, variables);
+        self.parts.pop();
+        self.part_variables.pop();
+
+        yml
+    }
+
+    /// `!mix` values met while loading, to inject into the loaded value.
+    pub fn mixins(&self) -> &MixIns {
+        &self.mixins
+    }
+
+    /// Things that did not stop the loading but may be mistakes, like a missing optional part.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
 
+    /// Injects variables, trims mixins and visits `yml` as part of the part being loaded.
+    fn assemble(&mut self, yml: Value, variables: &Variables) -> AppResult<Value> {
+        let (yml, mixins) = parse_yml_part(yml, variables)?;
+
+        if let Some(identifier) = self.parts.last().cloned() {
+            self.record_transform_origins(&identifier, &yml, &mixins);
+            mixins.keys().for_each(|key| {
+                self.graph.mixins.insert(MixinEdge {
+                    part: identifier.clone(),
+                    key: key.clone(),
+                });
+            });
+        }
+
+        let identifier = self.parts.last().cloned().unwrap_or_default();
         let mixins = mixins
             .iter()
             .map(|(key, values)| {
@@ -32,36 +125,82 @@ impl YmlAggregator {
                     .iter()
                     .map(|value| {
                         let mut aggregator = YmlAggregator::new(Arc::clone(&self.reader));
-                        let value = aggregator.visit(value, &variables)?;
+                        aggregator.parts = self.parts.clone();
+                        aggregator.part_variables = self.part_variables.clone();
+                        let value = aggregator.visit(value, variables)?;
                         let mut mixins = aggregator.mixins;
                         mixins.add(key.clone(), vec![value]);
-                        Ok(mixins)
+                        let mut mixin_parts = aggregator.mixin_parts;
+                        mixin_parts
+                            .entry(key.clone())
+                            .or_default()
+                            .push(identifier.clone());
+                        self.graph.merge(aggregator.graph);
+                        self.warnings.extend(aggregator.warnings);
+                        Ok((mixins, mixin_parts, aggregator.transform_origins))
                     })
-                    .collect::<AppResult<Vec<MixIns>>>()?;
+                    .collect::<AppResult<Vec<_>>>()?;
 
-                let sub_mixins = sub_mixins.iter().fold(MixIns::new(), |mut acc, mix| {
-                    acc.merge(mix);
-                    acc
-                });
+                let mut merged = (MixIns::new(), HashMap::<String, Vec<String>>::new());
+                sub_mixins
+                    .into_iter()
+                    .for_each(|(mixins, mixin_parts, origins)| {
+                        merged.0.merge(&mixins);
+                        mixins.keys().for_each(|key| {
+                            let parts = mixin_parts.get(key).cloned().unwrap_or_default();
+                            merged.1.entry(key.clone()).or_default().extend(parts);
+                        });
+                        origins.into_iter().for_each(|(formula, parts)| {
+                            self.transform_origins
+                                .entry(formula)
+                                .or_default()
+                                .extend(parts);
+                        });
+                    });
 
-                Ok(sub_mixins)
+                Ok(merged)
             })
-            .collect::<AppResult<Vec<MixIns>>>()?;
+            .collect::<AppResult<Vec<(MixIns, HashMap<String, Vec<String>>)>>>()?;
 
-        let mixins = mixins.iter().fold(MixIns::new(), |mut acc, mix| {
-            acc.merge(mix);
-            acc
+        mixins.into_iter().for_each(|(mixins, mixin_parts)| {
+            mixins.iter().for_each(|(key, value)| {
+                self.mixins.add(key.clone(), value.clone());
+                let parts = mixin_parts.get(key).cloned().unwrap_or_default();
+                self.mixin_parts
+                    .entry(key.clone())
+                    .or_default()
+                    .extend(parts);
+            });
         });
 
-        mixins.iter().for_each(|(key, value)| {
-            self.mixins.add(key.clone(), value.clone());
-        });
-
-        let yml = self.visit(&yml, &variables)?;
+        let yml = self.visit(&yml, variables)?;
         Ok(yml)
     }
 
-    pub fn visit(&mut self, val: &Value, variables: &Variables) -> AppResult<Value> {
+    fn record_transform_origins(&mut self, identifier: &str, yml: &Value, mixins: &MixIns) {
+        let own_transforms = yml.get(Self::TRANSFORM_KEY).into_iter();
+        let mixed_transforms = mixins
+            .iter()
+            .filter(|(key, _)| {
+                key.as_str() == Self::TRANSFORM_KEY
+                    || key.starts_with(&format!("{}.", Self::TRANSFORM_KEY))
+            })
+            .flat_map(|(_, values)| values.iter());
+
+        let mut formulas = vec![];
+        own_transforms
+            .chain(mixed_transforms)
+            .for_each(|value| collect_formulas(value, &mut formulas));
+
+        formulas.into_iter().for_each(|formula| {
+            self.transform_origins
+                .entry(formula)
+                .or_default()
+                .push(identifier.to_string());
+        });
+    }
+
+    fn visit(&mut self, val: &Value, variables: &Variables) -> AppResult<Value> {
         match val {
             Value::Tagged(t) => self.on_tag(t, variables),
             Value::Mapping(map) => self.on_mapping(map, variables),
@@ -74,34 +213,336 @@ impl YmlAggregator {
         let tag = val.tag.to_string();
         let value = &val.value;
 
-        match tag.starts_with(Self::INCLUDE_TAG_PREFIX) {
-            true => {
-                let file = tag.trim_start_matches(Self::INCLUDE_TAG_PREFIX);
-                let new_variables: Variables = value.clone().try_into()?;
-                let mut variables = variables.clone();
+        if tag == Self::EACH_TAG {
+            return self.on_each(value, variables);
+        }
+
+        if let Some(file) = tag.strip_prefix(Self::INCLUDE_TAG_PREFIX) {
+            return self.on_include(file, value, variables, false, false);
+        }
+
+        if let Some(file) = tag.strip_prefix(Self::INCLUDE_OPTIONAL_TAG_PREFIX) {
+            return self.on_include(file, value, variables, false, true);
+        }
+
+        if let Some(file) = tag.strip_prefix(Self::INCLUDE_MAP_TAG_PREFIX) {
+            return self.on_include(file, value, variables, true, false);
+        }
+
+        if let Some((format, file)) = ResourceFormat::from_tag(&tag) {
+            return self.on_resource(format, file);
+        }
+
+        let yml = self.visit(value, variables)?;
+        Ok(Value::Tagged(Box::new(TaggedValue {
+            tag: Tag::new(tag),
+            value: yml,
+        })))
+    }
+
+    /// Includes one part, or every part matching a glob pattern with the same variables.
+    /// Matching parts are gathered in a sequence ordered by identifier, or in a mapping
+    /// keyed by file stem when `as_mapping` is set. An `optional` include of a missing part
+    /// gives `Null`, parts it includes in turn must still exist.
+    fn on_include(
+        &mut self,
+        file: &str,
+        value: &Value,
+        variables: &Variables,
+        as_mapping: bool,
+        optional: bool,
+    ) -> AppResult<Value> {
+        let (file, tag_selector) = match file.split_once(Self::SELECTOR_SEPARATOR) {
+            Some((file, selector)) => (file, Some(selector.to_string())),
+            None => (file, None),
+        };
+
+        let mut new_variables: Variables = value.clone().try_into()?;
+        let selector = match new_variables.remove(Self::SELECT_KEY) {
+            Some(Value::String(selector)) => Some(selector),
+            None => tag_selector,
+            Some(selector) => Err(AppError::ParseYml(format!(
+                "{} should be a path like a.0.b, got {selector:?}",
+                Self::SELECT_KEY
+            )))?,
+        };
+
+        let mut variables = variables.clone();
+        for (key, value) in new_variables.iter() {
+            variables.insert(key.clone(), value.clone());
+        }
+
+        let file = resolve_relative(self.parts.last().map(|p| p.as_str()), file);
+        let is_glob = file.contains(['*', '?', '[']);
+
+        if !is_glob && !as_mapping {
+            return match self.include_part(&file, selector.as_deref(), &variables, &new_variables) {
+                Err(AppError::MissingPart(part)) if optional && part == file => {
+                    self.warnings.push(match self.parts.last() {
+                        Some(from) => format!("Optional part {file} included by {from} not found"),
+                        None => format!("Optional part {file} not found"),
+                    });
+                    Ok(Value::Null)
+                }
+                result => result,
+            };
+        }
+
+        let mut files = match is_glob {
+            true => self.reader.get_filepathes_from_glob(&file)?,
+            false => vec![file],
+        };
+        // A part matching its own glob pattern does not include itself.
+        if let (true, Some(including)) = (is_glob, self.parts.last()) {
+            files.retain(|file| file != including);
+        }
+        files.sort();
+
+        let mut new_seq: Vec<Value> = vec![];
+        let mut new_map = Mapping::new();
+        for file in files {
+            let stem = file.rsplit('/').next().unwrap_or(&file).to_string();
+            self.path.push(match as_mapping {
+                true => stem.clone(),
+                false => new_seq.len().to_string(),
+            });
+            let yml = self.include_part(&file, selector.as_deref(), &variables, &new_variables);
+            self.path.pop();
+            let yml = yml?;
+            if let Value::Null = yml {
+                continue;
+            }
+
+            match as_mapping {
+                true => {
+                    if new_map.contains_key(&stem) {
+                        Err(AppError::ParseYml(format!(
+                            "Several included parts would be keyed {stem}, {file} is one of them"
+                        )))?;
+                    }
+                    new_map.insert(Value::String(stem), yml);
+                }
+                false => new_seq.push(yml),
+            }
+        }
+
+        match (as_mapping, new_seq.is_empty(), new_map.is_empty()) {
+            (true, _, false) => Ok(Value::Mapping(new_map)),
+            (false, false, _) => Ok(Value::Sequence(new_seq)),
+            _ => Ok(Value::Null),
+        }
+    }
+
+    /// Adds to the graph every part `identifier` may include, including the ones behind
+    /// conditions or loops, and follows them in turn. Nothing is evaluated, paths written
+    /// with variables missing from `variables` are skipped.
+    pub(crate) fn collect_includes(
+        &mut self,
+        identifier: &str,
+        variables: &Variables,
+    ) -> AppResult<()> {
+        let mut visited = vec![];
+        self.collect_part_includes(identifier, variables, &mut visited)
+    }
+
+    fn collect_part_includes(
+        &mut self,
+        identifier: &str,
+        variables: &Variables,
+        visited: &mut Vec<String>,
+    ) -> AppResult<()> {
+        if visited.iter().any(|part| part == identifier) {
+            return Ok(());
+        }
+        visited.push(identifier.to_string());
+        let yml = self.reader.get_value(identifier)?;
 
-                for (key, value) in new_variables.iter() {
-                    variables.insert(key.clone(), value.clone());
+        let mut includes = vec![];
+        collect_include_tags(&yml, &mut includes);
+        for (file, value, optional) in includes {
+            let file = match variables.substitute(&file)? {
+                Value::String(file) if !file.contains('$') => file,
+                Value::String(_) => continue,
+                value => {
+                    self.graph.failures.insert(
+                        identifier.to_string(),
+                        format!("Include path {file} is not a part name: {value:?}"),
+                    );
+                    continue;
                 }
+            };
+            let file = file
+                .split_once(Self::SELECTOR_SEPARATOR)
+                .map_or(file.as_str(), |(file, _)| file);
+            let file = resolve_relative(Some(identifier), file);
+
+            let mut files = match file.contains(['*', '?', '[']) {
+                true => self.reader.get_filepathes_from_glob(&file)?,
+                false => vec![file],
+            };
+            files.retain(|file| file != identifier);
 
-                let yml = self.load(file, &variables)?;
-                let yml = self.visit(&yml, &variables)?;
-                Ok(yml)
+            let mut edge_variables: Variables = value.try_into().unwrap_or_default();
+            edge_variables.remove(Self::SELECT_KEY);
+            let mut part_variables = variables.clone();
+            for (key, value) in edge_variables.iter() {
+                part_variables.insert(key.clone(), value.clone());
             }
-            false => {
-                let yml = self.visit(value, variables)?;
-                Ok(Value::Tagged(Box::new(TaggedValue {
-                    tag: Tag::new(tag),
-                    value: yml,
-                })))
+
+            for file in files {
+                let known = self
+                    .graph
+                    .includes
+                    .iter()
+                    .any(|edge| edge.from == identifier && edge.to == file);
+                if !known {
+                    self.graph.add_include(IncludeEdge {
+                        from: identifier.to_string(),
+                        to: file.clone(),
+                        variables: edge_variables
+                            .iter()
+                            .map(|(key, value)| (key.clone(), value.clone()))
+                            .collect(),
+                    });
+                }
+                match self.collect_part_includes(&file, &part_variables, visited) {
+                    Err(AppError::MissingPart(part)) if optional && part == file => {}
+                    result => result?,
+                }
             }
         }
+
+        Ok(())
+    }
+
+    fn on_resource(&self, format: ResourceFormat, file: &str) -> AppResult<Value> {
+        let file = resolve_relative(self.parts.last().map(|p| p.as_str()), file);
+        let content = self.reader.get_resource(&file)?;
+        format
+            .parse(&content)
+            .map_err(|e| AppError::ParseYml(format!("Could not parse {file}: {e}")))
+    }
+
+    /// Loads `file` with `variables`, `edge_variables` being the ones given by this include.
+    fn include_part(
+        &mut self,
+        file: &str,
+        selector: Option<&str>,
+        variables: &Variables,
+        edge_variables: &Variables,
+    ) -> AppResult<Value> {
+        let yml = self.load(file, variables)?;
+        self.part_paths
+            .push((self.path.join("."), file.to_string()));
+        if let Some(from) = self.parts.last() {
+            self.graph.add_include(IncludeEdge {
+                from: from.clone(),
+                to: file.to_string(),
+                variables: edge_variables
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect(),
+            });
+        }
+        let yml = self.visit(&yml, variables)?;
+
+        match selector {
+            Some(selector) => select(yml, selector).ok_or_else(|| {
+                AppError::ParseYml(format!("Nothing to select at {selector} in {file}"))
+            }),
+            None => Ok(yml),
+        }
+    }
+
+    /// Instantiates `do` once per item of `in`, which is either a sequence or a range
+    /// like `1..12` or `1..=12`. The item is bound to the `as` variable (default `item`)
+    /// and its position to the optional `index` variable. Items are gathered in a
+    /// sequence, or in a mapping when a `key` is given.
+    fn on_each(&mut self, val: &Value, variables: &Variables) -> AppResult<Value> {
+        let tag = Self::EACH_TAG;
+        let each = val.as_mapping().ok_or_else(|| {
+            AppError::ParseYml(format!("{tag} expects a mapping with in, as and do"))
+        })?;
+
+        let items = match each
+            .get("in")
+            .map(|items| variables.inject(items))
+            .transpose()?
+        {
+            Some(Value::Sequence(seq)) => seq,
+            Some(Value::String(range)) => parse_range(&range)?,
+            Some(Value::Null) => vec![],
+            None => Err(AppError::ParseYml(format!("{tag} is missing its in")))?,
+            Some(items) => Err(AppError::ParseYml(format!(
+                "{tag} can't iterate over {items:?}, use a sequence or a range"
+            )))?,
+        };
+        let item_name = match each.get("as") {
+            Some(Value::String(name)) => name.clone(),
+            None => "item".to_string(),
+            Some(name) => Err(AppError::ParseYml(format!(
+                "{tag} variable name should be a string, got {name:?}"
+            )))?,
+        };
+        let index_name = match each.get("index") {
+            Some(Value::String(name)) => Some(name.clone()),
+            None => None,
+            Some(name) => Err(AppError::ParseYml(format!(
+                "{tag} index name should be a string, got {name:?}"
+            )))?,
+        };
+        let body = each
+            .get("do")
+            .ok_or_else(|| AppError::ParseYml(format!("{tag} is missing its do")))?;
+        let key = each.get("key");
+
+        let mut new_seq: Vec<Value> = vec![];
+        let mut new_map = Mapping::new();
+        for (index, item) in items.into_iter().enumerate() {
+            let mut variables = variables.clone();
+            variables.insert(item_name.clone(), item);
+            if let Some(index_name) = &index_name {
+                variables.insert(index_name.clone(), Value::from(index));
+            }
+
+            let yml = self.assemble(body.clone(), &variables)?;
+            if let Value::Null = yml {
+                continue;
+            }
+
+            match key {
+                Some(key) => {
+                    let key = match variables.inject(key)? {
+                        Value::String(key) => key,
+                        Value::Number(n) => n.to_string(),
+                        key => Err(AppError::ParseYml(format!(
+                            "{key:?} can't be used as mapping key"
+                        )))?,
+                    };
+                    new_map.insert(Value::String(key), yml);
+                }
+                None => new_seq.push(yml),
+            }
+        }
+
+        match (key, new_seq.is_empty(), new_map.is_empty()) {
+            (Some(_), _, false) => Ok(Value::Mapping(new_map)),
+            (None, false, _) => Ok(Value::Sequence(new_seq)),
+            _ => Ok(Value::Null),
+        }
     }
 
     fn on_mapping(&mut self, val: &Mapping, variables: &Variables) -> AppResult<Value> {
         let mut new_map = Mapping::new();
         for (key, value) in val {
-            let yml = self.visit(&value, variables)?;
+            self.path.push(match key {
+                Value::String(key) => key.clone(),
+                Value::Number(key) => key.to_string(),
+                key => format!("{key:?}"),
+            });
+            let yml = self.visit(value, variables);
+            self.path.pop();
+            let yml = yml?;
             if let Value::Null = yml {
                 continue;
             }
@@ -116,7 +557,10 @@ impl YmlAggregator {
     fn on_sequence(&mut self, val: &Vec<Value>, variables: &Variables) -> AppResult<Value> {
         let mut new_seq: Vec<Value> = vec![];
         for value in val {
-            let yml = self.visit(&value, variables)?;
+            self.path.push(new_seq.len().to_string());
+            let yml = self.visit(value, variables);
+            self.path.pop();
+            let yml = yml?;
             if let Value::Null = yml {
                 continue;
             }
@@ -131,10 +575,199 @@ impl YmlAggregator {
 
 fn parse_yml_part(part: Value, variables: &Variables) -> AppResult<(Value, MixIns)> {
     let part = variables.inject(&part)?;
+    let part = variables.resolve_conditions(&part)?;
     let mut mixin = MixIns::new();
     let part = mixin.trim(&part)?;
 
-    return Ok((part, mixin));
+    Ok((part, mixin))
+}
+
+/// Non part files included as data: `!text::notes/intro.md` gives the file content as a string,
+/// `!json::`, `!yaml::` its parsed content and `!csv::` a sequence of mappings keyed by header.
+enum ResourceFormat {
+    Text,
+    Json,
+    Yaml,
+    Csv,
+}
+
+impl ResourceFormat {
+    fn from_tag(tag: &str) -> Option<(Self, &str)> {
+        [
+            ("!text::", ResourceFormat::Text),
+            ("!json::", ResourceFormat::Json),
+            ("!yaml::", ResourceFormat::Yaml),
+            ("!csv::", ResourceFormat::Csv),
+        ]
+        .into_iter()
+        .find_map(|(prefix, format)| tag.strip_prefix(prefix).map(|file| (format, file)))
+    }
+
+    fn parse(&self, content: &str) -> anyhow::Result<Value> {
+        let value = match self {
+            ResourceFormat::Text => Value::String(content.to_string()),
+            ResourceFormat::Json => {
+                serde_yaml::to_value(serde_json::from_str::<serde_json::Value>(content)?)?
+            }
+            ResourceFormat::Yaml => serde_yaml::from_str(content)?,
+            ResourceFormat::Csv => {
+                let mut reader = csv::Reader::from_reader(content.as_bytes());
+                let headers = reader.headers()?.clone();
+                let rows = reader
+                    .records()
+                    .map(|record| {
+                        let record = record?;
+                        let row = headers
+                            .iter()
+                            .zip(record.iter())
+                            .map(|(header, field)| (Value::from(header), csv_field(field)))
+                            .collect::<Mapping>();
+                        Ok(Value::Mapping(row))
+                    })
+                    .collect::<anyhow::Result<Vec<Value>>>()?;
+                Value::Sequence(rows)
+            }
+        };
+        Ok(value)
+    }
+}
+
+/// Numbers and booleans are only read as such when written the way they would be output,
+/// `007`, `1e3` or `nan` stay strings.
+fn csv_field(field: &str) -> Value {
+    let round_trips =
+        |value: &Value| serde_yaml::to_string(value).is_ok_and(|output| output.trim_end() == field);
+    if let Ok(n) = field.parse::<i64>() {
+        if round_trips(&Value::from(n)) {
+            return Value::from(n);
+        }
+    }
+    if let Ok(n) = field.parse::<f64>() {
+        if n.is_finite() && round_trips(&Value::from(n)) {
+            return Value::from(n);
+        }
+    }
+    match field {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        "" => Value::Null,
+        field => Value::from(field),
+    }
+}
+
+/// Every include written in `yml`, whatever its condition, with its variables and whether
+/// it is optional.
+fn collect_include_tags(yml: &Value, includes: &mut Vec<(String, Value, bool)>) {
+    match yml {
+        Value::Tagged(tagged) => {
+            let tag = tagged.tag.to_string();
+            let include = [
+                (YmlAggregator::INCLUDE_TAG_PREFIX, false),
+                (YmlAggregator::INCLUDE_OPTIONAL_TAG_PREFIX, true),
+                (YmlAggregator::INCLUDE_MAP_TAG_PREFIX, false),
+            ]
+            .into_iter()
+            .find_map(|(prefix, optional)| Some((tag.strip_prefix(prefix)?, optional)))
+            .or_else(|| {
+                let conditional = tag.strip_prefix(Variables::INCLUDE_IF_TAG_PREFIX)?;
+                Some((conditional.split_once("::")?.1, false))
+            });
+            if let Some((file, optional)) = include {
+                includes.push((file.to_string(), tagged.value.clone(), optional));
+            }
+            collect_include_tags(&tagged.value, includes);
+        }
+        Value::Mapping(map) => map
+            .values()
+            .for_each(|value| collect_include_tags(value, includes)),
+        Value::Sequence(seq) => seq
+            .iter()
+            .for_each(|value| collect_include_tags(value, includes)),
+        _ => {}
+    }
+}
+
+/// Resolves `./part` and `../part` from the directory of the `including` part.
+/// Other identifiers are relative to the root and kept as is.
+fn resolve_relative(including: Option<&str>, identifier: &str) -> String {
+    if !identifier.starts_with("./") && !identifier.starts_with("../") {
+        return identifier.to_string();
+    }
+
+    let mut segments: Vec<&str> = including.unwrap_or_default().split('/').collect();
+    segments.pop();
+
+    for segment in identifier.split('/') {
+        match segment {
+            "" | "." => {}
+            // An aliased part can't climb out of its alias, the reader confines what is left.
+            ".." => match segments.last() {
+                Some(last) if *last != ".." && !(segments.len() == 1 && last.starts_with('@')) => {
+                    segments.pop();
+                }
+                _ => segments.push(".."),
+            },
+            segment => segments.push(segment),
+        }
+    }
+
+    segments.join("/")
+}
+
+fn select(yml: Value, path: &str) -> Option<Value> {
+    path.split('.').try_fold(yml, |node, segment| match node {
+        Value::Mapping(mut map) => map.remove(segment),
+        Value::Sequence(mut seq) => match segment.parse::<usize>() {
+            Ok(index) if index < seq.len() => Some(seq.swap_remove(index)),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+/// Includes nested deeper than this are taken for a recursion that never ends.
+const MAX_INCLUDE_DEPTH: usize = 100;
+
+/// Ranges are expanded in memory, this keeps a typo from exhausting it.
+const MAX_RANGE_LEN: i64 = 100_000;
+
+fn parse_range(range: &str) -> AppResult<Vec<Value>> {
+    let regex = Regex::new(r"^\s*(-?\d+)\s*\.\.(=?)\s*(-?\d+)\s*$").map_err(AppError::other)?;
+    let captures = regex.captures(range).ok_or_else(|| {
+        AppError::ParseYml(format!(
+            "{range:?} is not a range, write it start..end or start..=end"
+        ))
+    })?;
+
+    let bound = |i: usize| {
+        captures[i]
+            .parse::<i64>()
+            .map_err(|e| AppError::ParseYml(format!("Invalid range bound in {range:?}: {e}")))
+    };
+    let (start, end) = (bound(1)?, bound(3)?);
+    let len = end
+        .saturating_sub(start)
+        .saturating_add(i64::from(&captures[2] == "="));
+    if len > MAX_RANGE_LEN {
+        Err(AppError::ParseYml(format!(
+            "{range:?} has more than {MAX_RANGE_LEN} items"
+        )))?;
+    }
+
+    let items = match &captures[2] {
+        "=" => (start..=end).map(Value::from).collect(),
+        _ => (start..end).map(Value::from).collect(),
+    };
+    Ok(items)
+}
+
+fn collect_formulas(value: &Value, formulas: &mut Vec<String>) {
+    match value {
+        Value::String(formula) => formulas.push(formula.clone()),
+        Value::Sequence(seq) => seq.iter().for_each(|v| collect_formulas(v, formulas)),
+        Value::Mapping(map) => map.values().for_each(|v| collect_formulas(v, formulas)),
+        _ => {}
+    }
 }
 
 #[cfg(test)]
@@ -142,14 +775,13 @@ mod test {
     use super::*;
 
     fn get_yml_part() -> &'static str {
-        let yml_part = r#"
+        r#"
             foo:
                 - $test
                 - $test2
             bar: !mix
                 - $test is $test2
-        "#;
-        yml_part
+        "#
     }
 
     #[test]
@@ -184,4 +816,74 @@ mod test {
         let bar_mixin = mixins.get("bar").unwrap();
         assert_eq!(bar_mixin, &vec![expected_mixins]);
     }
+
+    #[test]
+    fn it_should_parse_exclusive_and_inclusive_ranges() {
+        assert_eq!(
+            parse_range("1..4").unwrap(),
+            vec![Value::from(1), Value::from(2), Value::from(3)]
+        );
+        assert_eq!(
+            parse_range("-1..=1").unwrap(),
+            vec![Value::from(-1), Value::from(0), Value::from(1)]
+        );
+        assert!(parse_range("a..b").is_err());
+    }
+
+    #[test]
+    fn it_should_select_nested_node() {
+        let yml: Value = serde_yaml::from_str(
+            r#"
+            a:
+                - b: 1
+                - b: 2
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(select(yml.clone(), "a.1.b"), Some(Value::from(2)));
+        assert_eq!(select(yml.clone(), "a.2.b"), None);
+        assert_eq!(select(yml, "a.0.b.c"), None);
+    }
+
+    #[test]
+    fn it_should_resolve_relative_identifiers() {
+        let including = Some("stories/nested/birthday");
+
+        assert_eq!(resolve_relative(including, "./cake"), "stories/nested/cake");
+        assert_eq!(
+            resolve_relative(including, "../car_crash"),
+            "stories/car_crash"
+        );
+        assert_eq!(
+            resolve_relative(including, "../../tags/adult"),
+            "tags/adult"
+        );
+        assert_eq!(resolve_relative(including, "tags/adult"), "tags/adult");
+        assert_eq!(resolve_relative(None, "./simple_book"), "simple_book");
+        assert_eq!(
+            resolve_relative(Some("book"), "../shared/tag"),
+            "../shared/tag"
+        );
+    }
+
+    #[test]
+    fn it_should_only_convert_csv_fields_written_as_output() {
+        assert_eq!(csv_field("7"), Value::from(7));
+        assert_eq!(csv_field("-1.25"), Value::from(-1.25));
+        assert_eq!(csv_field("1.0"), Value::from(1.0));
+        assert_eq!(csv_field("true"), Value::Bool(true));
+        assert_eq!(csv_field(""), Value::Null);
+        for field in ["007", "+7", "1.50", "1e3", "nan", "NaN", "inf", "-0"] {
+            assert_eq!(csv_field(field), Value::from(field));
+        }
+    }
+
+    #[test]
+    fn it_should_keep_relative_identifiers_in_their_alias() {
+        let including = Some("@shared/tags/hero");
+
+        assert_eq!(resolve_relative(including, "../villain"), "@shared/villain");
+        assert_eq!(resolve_relative(including, "../../book"), "@shared/../book");
+    }
 }
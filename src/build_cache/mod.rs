@@ -0,0 +1,171 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    adapters::{Dependencies, PartReaderPort},
+    utils::result::{AppError, AppResult},
+};
+
+const PART_PREFIX: &str = "part:";
+const RESOURCE_PREFIX: &str = "resource:";
+const GLOB_PREFIX: &str = "glob:";
+const MISSING: &str = "missing";
+
+/// Reads through `reader`, keeping a hash of every part, resource and glob lookup.
+pub struct DependencyRecorder {
+    reader: Arc<dyn PartReaderPort>,
+    inputs: Mutex<BTreeMap<String, String>>,
+}
+
+impl DependencyRecorder {
+    pub fn new(reader: Arc<dyn PartReaderPort>) -> Self {
+        DependencyRecorder {
+            reader,
+            inputs: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn take_inputs(&self) -> AppResult<BTreeMap<String, String>> {
+        let mut inputs = self
+            .inputs
+            .lock()
+            .map_err(|e| AppError::FileSystem(format!("Could not read dependencies: {}", e)))?;
+        Ok(std::mem::take(&mut inputs))
+    }
+
+    fn record(&self, key: String, hash: String) -> AppResult<()> {
+        let mut inputs = self
+            .inputs
+            .lock()
+            .map_err(|e| AppError::FileSystem(format!("Could not write dependencies: {}", e)))?;
+        inputs.insert(key, hash);
+        Ok(())
+    }
+}
+
+impl PartReaderPort for DependencyRecorder {
+    fn get_value(&self, identifier: &str) -> AppResult<serde_yaml::Value> {
+        let value = self.reader.get_value(identifier);
+        if let Some(hash) = hash_part(&value)? {
+            self.record(format!("{PART_PREFIX}{identifier}"), hash)?;
+        }
+        value
+    }
+
+    fn get_filepathes_from_glob(&self, glob: &str) -> AppResult<Vec<String>> {
+        let filepathes = self.reader.get_filepathes_from_glob(glob)?;
+        self.record(format!("{GLOB_PREFIX}{glob}"), hash_glob(&filepathes))?;
+        Ok(filepathes)
+    }
+
+    fn get_resource(&self, identifier: &str) -> AppResult<String> {
+        let content = self.reader.get_resource(identifier)?;
+        self.record(format!("{RESOURCE_PREFIX}{identifier}"), hash(&content))?;
+        Ok(content)
+    }
+}
+
+pub fn dependencies(inputs: &BTreeMap<String, String>) -> Dependencies {
+    let mut dependencies = Dependencies::default();
+    for key in inputs.keys() {
+        if let Some(identifier) = key.strip_prefix(PART_PREFIX) {
+            dependencies.parts.insert(identifier.to_string());
+        } else if let Some(identifier) = key.strip_prefix(RESOURCE_PREFIX) {
+            dependencies.resources.insert(identifier.to_string());
+        } else if let Some(glob) = key.strip_prefix(GLOB_PREFIX) {
+            dependencies.globs.insert(glob.to_string());
+        }
+    }
+    dependencies
+}
+
+/// Whether every part, resource and glob recorded in `inputs` still hashes the same through `reader`.
+pub fn dependencies_unchanged(
+    reader: &dyn PartReaderPort,
+    inputs: &BTreeMap<String, String>,
+) -> AppResult<bool> {
+    for (key, recorded) in inputs {
+        let current = if let Some(identifier) = key.strip_prefix(PART_PREFIX) {
+            match hash_part(&reader.get_value(identifier))? {
+                Some(hash) => hash,
+                None => return Ok(false),
+            }
+        } else if let Some(identifier) = key.strip_prefix(RESOURCE_PREFIX) {
+            match reader.get_resource(identifier) {
+                Ok(content) => hash(&content),
+                Err(_) => return Ok(false),
+            }
+        } else if let Some(glob) = key.strip_prefix(GLOB_PREFIX) {
+            hash_glob(&reader.get_filepathes_from_glob(glob)?)
+        } else {
+            continue;
+        };
+
+        if current != *recorded {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+pub fn hash_variables(variables: &HashMap<String, String>) -> String {
+    let variables = variables.iter().collect::<BTreeMap<_, _>>();
+    hash(&format!("{variables:?}"))
+}
+
+pub fn hash_schema(schema_id: &str, schema: &serde_json::Value) -> String {
+    hash(&format!("{schema_id}\n{schema}"))
+}
+
+/// A part that could not be found is an input as well, it matters once it is created.
+/// Parts that could not be read for another reason have no hash.
+fn hash_part(value: &AppResult<serde_yaml::Value>) -> AppResult<Option<String>> {
+    match value {
+        Ok(value) => Ok(Some(hash(
+            &serde_yaml::to_string(value).map_err(AppError::other)?,
+        ))),
+        Err(AppError::MissingPart(_)) => Ok(Some(MISSING.to_string())),
+        Err(_) => Ok(None),
+    }
+}
+
+fn hash_glob(filepathes: &[String]) -> String {
+    let mut filepathes = filepathes.to_vec();
+    filepathes.sort();
+    hash(&filepathes.join("\n"))
+}
+
+fn hash(content: &str) -> String {
+    format!("{:016x}", xxhash_rust::xxh3::xxh3_64(content.as_bytes()))
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashMap, sync::Arc};
+
+    use crate::{
+        adapters::PartReaderPort, lib_infras::assembly_part_in_memory_reader::PartIMReader,
+    };
+
+    #[test]
+    fn it_should_detect_changed_and_created_parts() {
+        let reader = Arc::new(
+            PartIMReader::from_strings(HashMap::from([("book".to_string(), "a: 1".to_string())]))
+                .unwrap(),
+        );
+        let recorder = super::DependencyRecorder::new(reader.clone());
+        recorder.get_value("book").unwrap();
+        assert!(recorder.get_value("optional").is_err());
+        let inputs = recorder.take_inputs().unwrap();
+
+        assert_eq!(inputs.len(), 2);
+        assert!(super::dependencies_unchanged(reader.as_ref(), &inputs).unwrap());
+
+        reader
+            .insert_part("optional", serde_yaml::from_str("b: 2").unwrap())
+            .unwrap();
+        assert!(!super::dependencies_unchanged(reader.as_ref(), &inputs).unwrap());
+    }
+}
@@ -0,0 +1,120 @@
+use std::{path::PathBuf, sync::Arc};
+
+use crate::{
+    adapters::{
+        AssemblyOutputPort, BuildCachePort, PartReaderPort, PipelineStagePort, SchemaOutputPort,
+        SchemaReaderPort, TraceOutputPort,
+    },
+    lib_infras::{
+        assembly_fs_output::AssemblyFSOutput, assembly_part_fs_reader::PartFSReader,
+        schema_fs_output::SchemaFSOutput, schema_fs_reader::SchemaFSReader,
+    },
+    options::AssembleOptions,
+    App,
+};
+
+/// Builds an `App` reading parts and schemas in `root` and writing to `output`,
+/// unless other ports are given.
+pub struct AppBuilder {
+    root: PathBuf,
+    output_dir: PathBuf,
+    part_reader: Option<Arc<dyn PartReaderPort>>,
+    schema_reader: Option<Arc<dyn SchemaReaderPort>>,
+    assembly_output: Option<Arc<dyn AssemblyOutputPort>>,
+    schema_output: Option<Arc<dyn SchemaOutputPort>>,
+    trace_output: Option<Arc<dyn TraceOutputPort>>,
+    build_cache: Option<Arc<dyn BuildCachePort>>,
+    stages: Vec<Arc<dyn PipelineStagePort>>,
+    options: AssembleOptions,
+}
+
+impl AppBuilder {
+    pub fn new(root: PathBuf) -> Self {
+        AppBuilder {
+            root,
+            output_dir: PathBuf::from("output"),
+            part_reader: None,
+            schema_reader: None,
+            assembly_output: None,
+            schema_output: None,
+            trace_output: None,
+            build_cache: None,
+            stages: vec![],
+            options: AssembleOptions::default(),
+        }
+    }
+
+    /// Directory the default outputs write assembled entries and schemas in.
+    pub fn with_output_dir(mut self, output_dir: PathBuf) -> Self {
+        self.output_dir = output_dir;
+        self
+    }
+
+    pub fn with_part_reader(mut self, part_reader: Arc<dyn PartReaderPort>) -> Self {
+        self.part_reader = Some(part_reader);
+        self
+    }
+
+    pub fn with_schema_reader(mut self, schema_reader: Arc<dyn SchemaReaderPort>) -> Self {
+        self.schema_reader = Some(schema_reader);
+        self
+    }
+
+    pub fn with_assembly_output(mut self, assembly_output: Arc<dyn AssemblyOutputPort>) -> Self {
+        self.assembly_output = Some(assembly_output);
+        self
+    }
+
+    pub fn with_schema_output(mut self, schema_output: Arc<dyn SchemaOutputPort>) -> Self {
+        self.schema_output = Some(schema_output);
+        self
+    }
+
+    pub fn with_trace_output(mut self, trace_output: Arc<dyn TraceOutputPort>) -> Self {
+        self.trace_output = Some(trace_output);
+        self
+    }
+
+    pub fn with_build_cache(mut self, build_cache: Arc<dyn BuildCachePort>) -> Self {
+        self.build_cache = Some(build_cache);
+        self
+    }
+
+    pub fn with_stage(mut self, stage: Arc<dyn PipelineStagePort>) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    pub fn with_options(mut self, options: AssembleOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    pub fn build(self) -> App {
+        let log = self.options.log;
+        let app = App::new(
+            self.part_reader
+                .unwrap_or_else(|| Arc::new(PartFSReader::new(self.root.clone()).with_log(log))),
+            self.schema_reader
+                .unwrap_or_else(|| Arc::new(SchemaFSReader::new(self.root.clone()).with_log(log))),
+            self.assembly_output
+                .unwrap_or_else(|| Arc::new(AssemblyFSOutput::new(self.output_dir.clone()))),
+            self.schema_output
+                .unwrap_or_else(|| Arc::new(SchemaFSOutput::new(self.output_dir.clone()))),
+        )
+        .with_options(self.options);
+
+        let app = self
+            .stages
+            .into_iter()
+            .fold(app, |app, stage| app.with_stage(stage));
+        let app = match self.trace_output {
+            Some(trace_output) => app.with_trace_output(trace_output),
+            None => app,
+        };
+        match self.build_cache {
+            Some(build_cache) => app.with_build_cache(build_cache),
+            None => app,
+        }
+    }
+}
@@ -0,0 +1,374 @@
+use std::fmt::Display;
+
+use serde_yaml::Value;
+
+use crate::{
+    adapters::{PartReaderPort, TransformTrace},
+    aggregator::YmlAggregator,
+    mixins::MixIns,
+    options::MergePolicy,
+    utils::result::AppResult,
+};
+
+/// Where the value at `key` of an assembled entry came from, steps given in assembly order.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Explanation {
+    pub key: String,
+    pub value: Option<Value>,
+    pub steps: Vec<Provenance>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub enum Provenance {
+    /// The entry the value was assembled from.
+    Entry { part: String },
+    /// `part` was included by `from` with `!inc::`.
+    Include { from: String, part: String },
+    /// The value was written with variables, substituted when `part` was loaded.
+    Variable { part: String, expression: String },
+    /// A `!mix` on `key` declared in `part` brought the value.
+    Mixin { part: String, key: String },
+    /// A `_transform` formula assigned the value.
+    Transform {
+        formula: String,
+        part: Option<String>,
+        old_value: Option<Value>,
+        new_value: Value,
+    },
+}
+impl Display for Provenance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Provenance::Entry { part } => write!(f, "entry {part}"),
+            Provenance::Include { from, part } => {
+                write!(f, "included from {from} with !inc::{part}")
+            }
+            Provenance::Variable { part, expression } => {
+                write!(f, "variables substituted in \"{expression}\" from {part}")
+            }
+            Provenance::Mixin { part, key } => write!(f, "mixed into {key} with !mix from {part}"),
+            Provenance::Transform {
+                formula,
+                part,
+                old_value,
+                new_value,
+            } => {
+                let from = part.as_deref().unwrap_or("unknown part");
+                let inline = |value: &Value| {
+                    serde_json::to_string(value).unwrap_or_else(|_| format!("{value:?}"))
+                };
+                let old_value = old_value.as_ref().map(inline).unwrap_or("nothing".into());
+                write!(
+                    f,
+                    "_transform \"{formula}\" from {from}: {old_value} -> {}",
+                    inline(new_value)
+                )
+            }
+        }
+    }
+}
+
+/// What an entry was assembled from, to explain its keys with.
+pub(crate) struct Sources<'a> {
+    pub reader: &'a dyn PartReaderPort,
+    pub aggregator: &'a YmlAggregator,
+    /// The entry as loaded, before mixins are injected.
+    pub loaded: &'a Value,
+    pub entry: &'a str,
+}
+
+/// Traces `key` back through the parts the entry was loaded from, the mixins `owners`
+/// credits with it and the `traces` of the transformation that gave `assembled`.
+pub(crate) fn explain(
+    sources: &Sources,
+    key: &str,
+    owners: &Owners,
+    traces: &[TransformTrace],
+    assembled: &Value,
+) -> Explanation {
+    let Sources {
+        reader,
+        aggregator,
+        loaded,
+        entry,
+    } = sources;
+    let mut steps = vec![Provenance::Entry {
+        part: entry.to_string(),
+    }];
+
+    let owners = owners.at(&path(key));
+    if owners.contains(&&Owner::Base) {
+        let mut written_in = aggregator
+            .part_paths
+            .iter()
+            .filter(|(part_path, _)| is_prefix(part_path, key))
+            .collect::<Vec<_>>();
+        written_in.sort_by_key(|(part_path, _)| path(part_path).len());
+
+        let mut from = entry.to_string();
+        let mut at = String::new();
+        for (part_path, part) in written_in {
+            steps.push(Provenance::Include {
+                from: from.clone(),
+                part: part.clone(),
+            });
+            from = part.clone();
+            at = part_path.clone();
+        }
+
+        let relative = path(key)[path(&at).len()..].to_vec();
+        let raw = reader.get_value(&from).ok();
+        if let Some(Value::String(expression)) =
+            raw.as_ref().and_then(|raw| value_at(raw, &relative))
+        {
+            let substituted = value_at(loaded, &path(key))
+                .is_some_and(|value| value != &Value::String(expression.clone()));
+            if substituted {
+                steps.push(Provenance::Variable {
+                    part: from,
+                    expression: expression.clone(),
+                });
+            }
+        }
+    }
+
+    for owner in owners {
+        let Owner::Mix { key, index } = owner else {
+            continue;
+        };
+        let part = aggregator
+            .mixin_parts
+            .get(key)
+            .and_then(|parts| parts.get(*index))
+            .cloned()
+            .unwrap_or_default();
+        include_chain(aggregator, entry, &part)
+            .into_iter()
+            .for_each(|step| {
+                if !steps.contains(&step) {
+                    steps.push(step)
+                }
+            });
+        steps.push(Provenance::Mixin {
+            part,
+            key: key.clone(),
+        });
+    }
+
+    traces
+        .iter()
+        .filter(|trace| is_prefix(&trace.key, key) || is_prefix(key, &trace.key))
+        .for_each(|trace| {
+            steps.push(Provenance::Transform {
+                formula: trace.formula.clone(),
+                part: trace.part.clone(),
+                old_value: trace.old_value.clone(),
+                new_value: trace.new_value.clone(),
+            })
+        });
+
+    Explanation {
+        key: key.to_string(),
+        value: value_at(assembled, &path(key)).cloned(),
+        steps,
+    }
+}
+
+/// Includes leading from `entry` to `part`.
+fn include_chain(aggregator: &YmlAggregator, entry: &str, part: &str) -> Vec<Provenance> {
+    let mut chain = vec![];
+    let mut current = part.to_string();
+    while current != entry {
+        let Some(edge) = aggregator
+            .graph
+            .includes
+            .iter()
+            .find(|edge| edge.to == current && !chain.contains(&edge.from))
+        else {
+            break;
+        };
+        chain.push(edge.from.clone());
+        current = edge.from.clone();
+    }
+
+    let mut steps = vec![];
+    let mut to = part.to_string();
+    for from in chain {
+        steps.push(Provenance::Include {
+            from: from.clone(),
+            part: to,
+        });
+        to = from;
+    }
+    steps.reverse();
+    steps
+}
+
+fn path(key: &str) -> Vec<&str> {
+    key.split('.')
+        .filter(|segment| !segment.is_empty())
+        .collect()
+}
+
+fn is_prefix(prefix: &str, key: &str) -> bool {
+    prefix.is_empty() || key == prefix || key.starts_with(&format!("{prefix}."))
+}
+
+fn value_at<'a>(value: &'a Value, path: &[&str]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |value, segment| match value {
+        Value::Mapping(map) => map.get(*segment),
+        Value::Sequence(seq) => seq.get(segment.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Owner {
+    Base,
+    /// The `index`th value mixed into `key`.
+    Mix {
+        key: String,
+        index: usize,
+    },
+}
+
+/// Who gave each leaf of a value once mixins are injected into it.
+pub(crate) struct Owners {
+    /// The injected value with each leaf replaced by the index of its owner.
+    tree: Value,
+    owners: Vec<Owner>,
+}
+impl Owners {
+    /// Injects `mixins` into `loaded` like the assembly does, on owners instead of leaves.
+    pub fn new(mixins: &MixIns, loaded: &Value, merge: MergePolicy) -> AppResult<Self> {
+        let mut owners = vec![Owner::Base];
+        let base = Self::mark(loaded, 0);
+
+        let mut marked = MixIns::new();
+        for (key, values) in mixins.iter() {
+            let values = values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    owners.push(Owner::Mix {
+                        key: key.clone(),
+                        index,
+                    });
+                    Self::mark(value, owners.len() - 1)
+                })
+                .collect();
+            marked.add(key.clone(), values);
+        }
+
+        Ok(Owners {
+            tree: marked.inject_with(&base, merge)?,
+            owners,
+        })
+    }
+
+    /// `value` with its leaves replaced by `owner`, a null value is kept since merges skip it.
+    fn mark(value: &Value, owner: usize) -> Value {
+        fn leaves(value: &Value, owner: usize) -> Value {
+            match value {
+                Value::Mapping(map) => Value::Mapping(
+                    map.iter()
+                        .map(|(key, value)| (key.clone(), leaves(value, owner)))
+                        .collect(),
+                ),
+                Value::Sequence(seq) => {
+                    Value::Sequence(seq.iter().map(|value| leaves(value, owner)).collect())
+                }
+                _ => Value::from(owner),
+            }
+        }
+        match value {
+            Value::Null => Value::Null,
+            value => leaves(value, owner),
+        }
+    }
+
+    /// Owners of the leaves under `path`, the base one when nothing was mixed there.
+    fn at(&self, path: &[&str]) -> Vec<&Owner> {
+        fn collect(value: &Value, indexes: &mut Vec<usize>) {
+            match value {
+                Value::Mapping(map) => map.values().for_each(|value| collect(value, indexes)),
+                Value::Sequence(seq) => seq.iter().for_each(|value| collect(value, indexes)),
+                value => indexes.extend(value.as_u64().map(|index| index as usize)),
+            }
+        }
+
+        let mut indexes = vec![];
+        if let Some(value) = value_at(&self.tree, path) {
+            collect(value, &mut indexes);
+        }
+        indexes.sort();
+        indexes.dedup();
+        match indexes.is_empty() {
+            true => vec![&self.owners[0]],
+            false => indexes
+                .into_iter()
+                .filter_map(|index| self.owners.get(index))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn owners(base: &str, mixes: &[&str], merge: MergePolicy) -> Owners {
+        let mut mixins = MixIns::new();
+        mixins.add(
+            "colors".to_string(),
+            mixes
+                .iter()
+                .map(|mix| serde_yaml::from_str(mix).unwrap())
+                .collect(),
+        );
+        let base: Value = serde_yaml::from_str(base).unwrap();
+        Owners::new(&mixins, &base, merge).unwrap()
+    }
+
+    fn mix(index: usize) -> Owner {
+        Owner::Mix {
+            key: "colors".to_string(),
+            index,
+        }
+    }
+
+    #[test]
+    fn it_should_find_which_mixin_gave_a_sequence_item() {
+        let owners = owners(
+            "title: book",
+            &["[yellow]", "[red, black]", "rose"],
+            MergePolicy::Append,
+        );
+
+        assert_eq!(owners.at(&["colors", "0"]), vec![&mix(0)]);
+        assert_eq!(owners.at(&["colors", "2"]), vec![&mix(1)]);
+        assert_eq!(owners.at(&["colors", "3"]), vec![&mix(2)]);
+        assert_eq!(owners.at(&["title"]), vec![&Owner::Base]);
+    }
+
+    #[test]
+    fn it_should_find_which_mixin_gave_a_mapping_key() {
+        let owners = owners("colors:\n  a: 1\n  b: 2", &["b: 3"], MergePolicy::Append);
+
+        assert_eq!(owners.at(&["colors", "a"]), vec![&Owner::Base]);
+        assert_eq!(owners.at(&["colors", "b"]), vec![&mix(0)]);
+        assert_eq!(owners.at(&["colors"]), vec![&Owner::Base, &mix(0)]);
+    }
+
+    #[test]
+    fn it_should_find_which_mixin_replaced_a_value() {
+        let owners = owners(
+            "colors: [red]",
+            &["[yellow]", "[black, white]"],
+            MergePolicy::Replace,
+        );
+
+        assert_eq!(owners.at(&["colors", "0"]), vec![&mix(1)]);
+        assert_eq!(owners.at(&["colors"]), vec![&mix(1)]);
+    }
+}
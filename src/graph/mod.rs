@@ -0,0 +1,254 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_yaml::Value;
+
+use crate::utils::result::{AppError, AppResult};
+
+/// Entries, the parts they include with the variables given on each include,
+/// and the keys each part mixes values into.
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize)]
+pub struct IncludeGraph {
+    pub entries: BTreeSet<String>,
+    pub includes: Vec<IncludeEdge>,
+    pub mixins: BTreeSet<MixinEdge>,
+    /// Entries that could not be loaded, with the reason. Their includes are still given.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub failures: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct IncludeEdge {
+    pub from: String,
+    pub to: String,
+    pub variables: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, serde::Serialize)]
+pub struct MixinEdge {
+    pub part: String,
+    pub key: String,
+}
+
+impl IncludeGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every entry and part of the graph.
+    pub fn parts(&self) -> BTreeSet<&str> {
+        self.entries
+            .iter()
+            .map(|entry| entry.as_str())
+            .chain(
+                self.includes
+                    .iter()
+                    .flat_map(|edge| [edge.from.as_str(), edge.to.as_str()]),
+            )
+            .chain(self.mixins.iter().map(|edge| edge.part.as_str()))
+            .collect()
+    }
+
+    /// Entries including `part`, directly or through other parts.
+    pub fn entries_using(&self, part: &str) -> BTreeSet<String> {
+        let mut users = BTreeSet::from([part]);
+        let mut to_visit = vec![part];
+
+        while let Some(included) = to_visit.pop() {
+            for edge in self.includes.iter().filter(|edge| edge.to == included) {
+                if users.insert(edge.from.as_str()) {
+                    to_visit.push(edge.from.as_str());
+                }
+            }
+        }
+
+        self.entries
+            .iter()
+            .filter(|entry| users.contains(entry.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    pub fn merge(&mut self, other: IncludeGraph) {
+        self.entries.extend(other.entries);
+        other
+            .includes
+            .into_iter()
+            .for_each(|edge| self.add_include(edge));
+        self.mixins.extend(other.mixins);
+        self.failures.extend(other.failures);
+    }
+
+    pub(crate) fn add_include(&mut self, edge: IncludeEdge) {
+        if !self.includes.contains(&edge) {
+            self.includes.push(edge);
+        }
+    }
+
+    pub fn to_json(&self) -> AppResult<String> {
+        serde_json::to_string_pretty(self).map_err(AppError::other)
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut dot = vec!["digraph includes {".to_string()];
+        for part in self.parts() {
+            let shape = match self.entries.contains(part) {
+                true => "box",
+                false => "ellipse",
+            };
+            dot.push(format!("  \"{}\" [shape={shape}];", escape_dot(part)));
+        }
+        for key in self.mixin_keys() {
+            dot.push(format!(
+                "  \"key:{0}\" [label=\"{0}\", shape=note];",
+                escape_dot(key)
+            ));
+        }
+        for edge in self.includes.iter() {
+            dot.push(format!(
+                "  \"{}\" -> \"{}\" [label=\"{}\"];",
+                escape_dot(&edge.from),
+                escape_dot(&edge.to),
+                escape_dot(&variables_label(&edge.variables))
+            ));
+        }
+        for edge in self.mixins.iter() {
+            dot.push(format!(
+                "  \"{}\" -> \"key:{}\" [style=dashed];",
+                escape_dot(&edge.part),
+                escape_dot(&edge.key)
+            ));
+        }
+        dot.push("}".to_string());
+        dot.join("\n")
+    }
+
+    pub fn to_mermaid(&self) -> String {
+        let parts = self.parts().into_iter().collect::<Vec<&str>>();
+        let keys = self.mixin_keys().into_iter().collect::<Vec<&str>>();
+        let part_id = |part: &str| parts.iter().position(|p| *p == part).unwrap_or_default();
+        let key_id = |key: &str| keys.iter().position(|k| *k == key).unwrap_or_default();
+
+        let mut mermaid = vec!["flowchart LR".to_string()];
+        for (i, part) in parts.iter().enumerate() {
+            match self.entries.contains(*part) {
+                true => mermaid.push(format!("  p{i}[[\"{}\"]]", escape_mermaid(part))),
+                false => mermaid.push(format!("  p{i}[\"{}\"]", escape_mermaid(part))),
+            }
+        }
+        for (i, key) in keys.iter().enumerate() {
+            mermaid.push(format!("  k{i}[/\"{}\"/]", escape_mermaid(key)));
+        }
+        for edge in self.includes.iter() {
+            let label = variables_label(&edge.variables);
+            let arrow = match label.is_empty() {
+                true => "-->".to_string(),
+                false => format!("-->|\"{}\"|", escape_mermaid(&label)),
+            };
+            mermaid.push(format!(
+                "  p{} {arrow} p{}",
+                part_id(&edge.from),
+                part_id(&edge.to)
+            ));
+        }
+        for edge in self.mixins.iter() {
+            mermaid.push(format!(
+                "  p{} -.-> k{}",
+                part_id(&edge.part),
+                key_id(&edge.key)
+            ));
+        }
+        mermaid.join("\n")
+    }
+
+    fn mixin_keys(&self) -> BTreeSet<&str> {
+        self.mixins.iter().map(|edge| edge.key.as_str()).collect()
+    }
+}
+
+fn variables_label(variables: &BTreeMap<String, Value>) -> String {
+    variables
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::String(value) => value.clone(),
+                value => serde_yaml::to_string(value)
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string(),
+            };
+            format!("{key}={value}")
+        })
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+fn escape_dot(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_mermaid(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use super::{IncludeEdge, IncludeGraph, MixinEdge};
+
+    fn get_graph() -> IncludeGraph {
+        let mut graph = IncludeGraph::new();
+        graph.entries.insert("book".to_string());
+        graph.add_include(IncludeEdge {
+            from: "book".to_string(),
+            to: "tags/adult".to_string(),
+            variables: BTreeMap::from([("AGE".to_string(), serde_yaml::Value::from(18))]),
+        });
+        graph.mixins.insert(MixinEdge {
+            part: "tags/adult".to_string(),
+            key: "tags".to_string(),
+        });
+        graph
+    }
+
+    #[test]
+    fn it_should_find_entries_using_a_part() {
+        let mut graph = get_graph();
+        graph.entries.insert("other_book".to_string());
+        graph.entries.insert("unrelated_book".to_string());
+        graph.add_include(IncludeEdge {
+            from: "other_book".to_string(),
+            to: "book".to_string(),
+            variables: BTreeMap::new(),
+        });
+
+        let users = graph.entries_using("tags/adult");
+        assert_eq!(
+            users.into_iter().collect::<Vec<String>>(),
+            vec!["book", "other_book"]
+        );
+    }
+
+    #[test]
+    fn it_should_render_dot() {
+        let expected = r#"digraph includes {
+  "book" [shape=box];
+  "tags/adult" [shape=ellipse];
+  "key:tags" [label="tags", shape=note];
+  "book" -> "tags/adult" [label="AGE=18"];
+  "tags/adult" -> "key:tags" [style=dashed];
+}"#;
+        assert_eq!(get_graph().to_dot(), expected);
+    }
+
+    #[test]
+    fn it_should_render_mermaid() {
+        let expected = r#"flowchart LR
+  p0[["book"]]
+  p1["tags/adult"]
+  k0[/"tags"/]
+  p0 -->|"AGE=18"| p1
+  p1 -.-> k0"#;
+        assert_eq!(get_graph().to_mermaid(), expected);
+    }
+}
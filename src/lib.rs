@@ -1,6 +1,8 @@
 use adapters::AssemblyOutputFormat;
+pub use builder::AppBuilder;
 use jsonschema::JSONSchema;
-use std::collections::HashMap;
+use options::{AssembleOptions, EvaluationMode};
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
 use std::sync::Arc;
 use transformable::TransformableList;
@@ -9,12 +11,17 @@ use utils::result::AppResult;
 use variables::Variables;
 
 pub mod adapters;
-mod aggregator;
+pub mod aggregator;
+mod build_cache;
+pub mod builder;
+pub mod explain;
+pub mod graph;
 pub mod lib_infras;
-mod mixins;
-mod transformable;
+pub mod mixins;
+pub mod options;
+pub mod transformable;
 pub mod utils;
-mod variables;
+pub mod variables;
 
 #[derive(Clone)]
 pub struct App {
@@ -22,6 +29,10 @@ pub struct App {
     schema_reader: Arc<dyn adapters::SchemaReaderPort>,
     assembly_output: Arc<dyn adapters::AssemblyOutputPort>,
     schema_output: Arc<dyn adapters::SchemaOutputPort>,
+    trace_output: Option<Arc<dyn adapters::TraceOutputPort>>,
+    build_cache: Option<Arc<dyn adapters::BuildCachePort>>,
+    stages: Vec<Arc<dyn adapters::PipelineStagePort>>,
+    options: AssembleOptions,
 }
 
 impl App {
@@ -36,9 +47,43 @@ impl App {
             schema_reader,
             assembly_output,
             schema_output,
+            trace_output: None,
+            build_cache: None,
+            stages: vec![],
+            options: AssembleOptions::default(),
         }
     }
 
+    /// Runs `stage` hooks around each step of the assembly, after the stages added before it.
+    /// With a build cache, a stage should give the same value for the same inputs.
+    pub fn with_stage(mut self, stage: Arc<dyn adapters::PipelineStagePort>) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    pub fn builder(root: PathBuf) -> AppBuilder {
+        AppBuilder::new(root)
+    }
+
+    /// Options used when none are given, the format given to `compile_and_validate_yml` wins.
+    pub fn with_options(mut self, options: AssembleOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Records every assignment done by `_transform` formulas and hands them to `trace_output`.
+    pub fn with_trace_output(mut self, trace_output: Arc<dyn adapters::TraceOutputPort>) -> Self {
+        self.trace_output = Some(trace_output);
+        self
+    }
+
+    /// Skips entries whose parts, resources, schema and variables did not change since the
+    /// record `build_cache` kept of their last assembly, outputting that record instead.
+    pub fn with_build_cache(mut self, build_cache: Arc<dyn adapters::BuildCachePort>) -> Self {
+        self.build_cache = Some(build_cache);
+        self
+    }
+
     pub fn compile_and_validate_yml(
         &self,
         yml_id: &str,
@@ -46,45 +91,348 @@ impl App {
         variables: Option<HashMap<String, String>>,
         format: &AssemblyOutputFormat,
     ) -> AppResult<()> {
-        let mut aggregator = aggregator::YmlAggregator::new(Arc::clone(&self.part_reader));
+        self.build(yml_id, schema_id, variables, format)?;
+        Ok(())
+    }
 
-        let variables: Variables = variables.unwrap_or(HashMap::new()).into();
-        let yml = aggregator.load(yml_id, &variables)?;
-        let mixins = aggregator.mixins;
-        let yml = mixins.inject(&yml)?;
+    /// Same as `compile_and_validate_yml`, giving the parts, resources and globs the entry was read from.
+    pub fn build(
+        &self,
+        yml_id: &str,
+        schema_id: Option<&str>,
+        variables: Option<HashMap<String, String>>,
+        format: &AssemblyOutputFormat,
+    ) -> AppResult<adapters::Dependencies> {
+        let options = self.options.clone().with_format(format.clone());
+        self.build_with(yml_id, schema_id, variables, &options)
+    }
+
+    /// Same as `build` with other options than the app ones.
+    pub fn build_with(
+        &self,
+        yml_id: &str,
+        schema_id: Option<&str>,
+        variables: Option<HashMap<String, String>>,
+        options: &AssembleOptions,
+    ) -> AppResult<adapters::Dependencies> {
+        let variables = variables.unwrap_or_default();
+        let assembly = self.assemble_with(yml_id, schema_id, Some(variables.clone()), options)?;
+        if options.log {
+            assembly
+                .warnings
+                .iter()
+                .for_each(|warning| println!("warning: {}", warning));
+        }
+
+        let context = adapters::StageContext {
+            entry: yml_id,
+            variables: &variables,
+            step: adapters::PipelineStep::Output,
+        };
+        let value = self.before_step(&context, assembly.value)?;
+        self.output(
+            yml_id,
+            value.clone(),
+            schema_id,
+            assembly.schema,
+            &assembly.metadata.traces,
+            &options.format,
+        )?;
+        self.after_step(&context, value)?;
+        Ok(assembly.metadata.dependencies)
+    }
+
+    /// Assembles and validates an entry without handing it to the output ports.
+    /// With a build cache, an up to date build is reused and a new one is stored in it.
+    pub fn assemble(
+        &self,
+        yml_id: &str,
+        schema_id: Option<&str>,
+        variables: Option<HashMap<String, String>>,
+    ) -> AppResult<adapters::Assembly> {
+        self.assemble_with(yml_id, schema_id, variables, &self.options)
+    }
+
+    /// Same as `assemble` with other options than the app ones.
+    pub fn assemble_with(
+        &self,
+        yml_id: &str,
+        schema_id: Option<&str>,
+        variables: Option<HashMap<String, String>>,
+        options: &AssembleOptions,
+    ) -> AppResult<adapters::Assembly> {
+        let variables = variables.unwrap_or_default();
+        let (mut inputs, schema_json) = self.build_inputs(schema_id, &variables, options)?;
+        let assembly = |record: adapters::BuildRecord, cached: bool| {
+            if options.strict && !record.warnings.is_empty() {
+                return Err(AppError::ValidateYml(format!(
+                    "Warnings while assembling {yml_id}:\n{}",
+                    record.warnings.join("\n")
+                )));
+            }
+            Ok(adapters::Assembly {
+                value: record.output,
+                schema: schema_json.clone(),
+                warnings: record.warnings,
+                metadata: adapters::AssemblyMetadata {
+                    entry: yml_id.to_string(),
+                    schema_id: schema_id.map(|schema_id| schema_id.to_string()),
+                    dependencies: build_cache::dependencies(&record.inputs),
+                    traces: record.traces,
+                    cached,
+                },
+            })
+        };
+
+        if let Some(build_cache) = &self.build_cache {
+            if let Some(record) = build_cache.get(yml_id)? {
+                let same_inputs = inputs
+                    .iter()
+                    .all(|(key, hash)| record.inputs.get(key) == Some(hash));
+                if same_inputs
+                    && build_cache::dependencies_unchanged(
+                        self.part_reader.as_ref(),
+                        &record.inputs,
+                    )?
+                {
+                    if options.log {
+                        println!("up to date: {}", yml_id);
+                    }
+                    return assembly(record, true);
+                }
+            }
+        }
+
+        let recorder = Arc::new(build_cache::DependencyRecorder::new(Arc::clone(
+            &self.part_reader,
+        )));
+        let (yml, traces, warnings) = self.assemble_entry(
+            recorder.clone(),
+            yml_id,
+            schema_json.as_ref(),
+            variables,
+            options,
+        )?;
+        inputs.extend(recorder.take_inputs()?);
+
+        let record = adapters::BuildRecord {
+            inputs,
+            output: yml,
+            traces,
+            warnings,
+        };
+        if let Some(build_cache) = &self.build_cache {
+            build_cache.set(yml_id, &record)?;
+        }
+
+        assembly(record, false)
+    }
+
+    /// Includes and mixins met while loading each entry, without transforming, validating
+    /// nor outputting them. Includes behind conditions are given whatever the variables,
+    /// an entry failing to load is reported in the graph failures.
+    pub fn graph(
+        &self,
+        entries: &[String],
+        variables: Option<HashMap<String, String>>,
+    ) -> AppResult<graph::IncludeGraph> {
+        let variables: Variables = variables.unwrap_or_default().into();
+        let mut graph = graph::IncludeGraph::new();
+
+        for entry in entries {
+            let mut aggregator = aggregator::YmlAggregator::new(Arc::clone(&self.part_reader));
+            let loaded = aggregator.load(entry, &variables).map(|_| ());
+            let collected = aggregator.collect_includes(entry, &variables);
+            if let Err(e) = loaded.and(collected) {
+                graph.failures.insert(entry.clone(), e.to_string());
+            }
+            graph.entries.insert(entry.clone());
+            graph.merge(aggregator.graph);
+        }
+
+        Ok(graph)
+    }
+
+    /// Where the value at the dotted `key` of the assembled entry came from: the parts it
+    /// was written in and included from, variables, mixins and `_transform` formulas.
+    pub fn explain(
+        &self,
+        yml_id: &str,
+        key: &str,
+        variables: Option<HashMap<String, String>>,
+    ) -> AppResult<explain::Explanation> {
+        let variables: Variables = variables.unwrap_or_default().into();
+        let mut aggregator = aggregator::YmlAggregator::new(Arc::clone(&self.part_reader));
+        let loaded = aggregator.load(yml_id, &variables)?;
+        let yml = aggregator.mixins.inject_with(&loaded, self.options.merge)?;
+        let owners = explain::Owners::new(&aggregator.mixins, &loaded, self.options.merge)?;
 
         let mut list = TransformableList::try_from(yml)?;
+        list.trace(aggregator.transform_origins.clone());
         list.transform()?;
-        let yml: serde_yaml::Value = list.try_into()?;
-
-        let schema_json = match schema_id {
-            Some(schema_id) => {
-                let yml_json_representation =
-                    serde_json::to_value(&yml).map_err(AppError::other)?;
-                let schema_json = self.schema_reader.get_validation_schema(schema_id)?;
-                let validator = JSONSchema::compile(&schema_json)
-                    .map_err(|e| AppError::ValidateYml(format!("Schema is not valid: {}", e)))?;
-                validator.validate(&yml_json_representation).map_err(|e| {
-                    let str_errors = e
-                        .into_iter()
-                        .map(|e| e.to_string())
-                        .collect::<Vec<String>>()
-                        .join("\n");
-
-                    AppError::ValidateYml(format!("Generated yml is not valid: {}", str_errors))
-                })?;
-
-                Some(schema_json)
+        let traces = list.take_traces();
+        let assembled: serde_yaml::Value = list.try_into()?;
+
+        let sources = explain::Sources {
+            reader: self.part_reader.as_ref(),
+            aggregator: &aggregator,
+            loaded: &loaded,
+            entry: yml_id,
+        };
+        Ok(explain::explain(
+            &sources, key, &owners, &traces, &assembled,
+        ))
+    }
+
+    /// Inputs of an entry known before assembling it.
+    fn build_inputs(
+        &self,
+        schema_id: Option<&str>,
+        variables: &HashMap<String, String>,
+        options: &AssembleOptions,
+    ) -> AppResult<(BTreeMap<String, String>, Option<serde_json::Value>)> {
+        let schema_json = schema_id
+            .map(|schema_id| self.schema_reader.get_validation_schema(schema_id))
+            .transpose()?;
+
+        let mut inputs = BTreeMap::new();
+        inputs.insert(
+            "variables".to_string(),
+            build_cache::hash_variables(variables),
+        );
+        inputs.insert(
+            "schema".to_string(),
+            match (schema_id, &schema_json) {
+                (Some(schema_id), Some(schema_json)) => {
+                    build_cache::hash_schema(schema_id, schema_json)
+                }
+                _ => "none".to_string(),
+            },
+        );
+        inputs.insert(
+            "traces".to_string(),
+            self.trace_output.is_some().to_string(),
+        );
+        inputs.insert(
+            "options".to_string(),
+            format!("{:?} {:?}", options.merge, options.evaluation),
+        );
+        inputs.insert("strict".to_string(), options.strict.to_string());
+        inputs.insert(
+            "stages".to_string(),
+            self.stages
+                .iter()
+                .map(|stage| stage.id())
+                .collect::<Vec<_>>()
+                .join(","),
+        );
+
+        Ok((inputs, schema_json))
+    }
+
+    fn assemble_entry(
+        &self,
+        part_reader: Arc<dyn adapters::PartReaderPort>,
+        yml_id: &str,
+        schema_json: Option<&serde_json::Value>,
+        variables: HashMap<String, String>,
+        options: &AssembleOptions,
+    ) -> AppResult<(
+        serde_yaml::Value,
+        Vec<adapters::TransformTrace>,
+        Vec<String>,
+    )> {
+        let mut aggregator = aggregator::YmlAggregator::new(Arc::clone(&part_reader));
+        let context = |step| adapters::StageContext {
+            entry: yml_id,
+            variables: &variables,
+            step,
+        };
+
+        let yml = part_reader.get_value(yml_id)?;
+        let yml = self.before_step(&context(adapters::PipelineStep::Aggregate), yml)?;
+        let yml = aggregator.load_value(yml_id, yml, &variables.clone().into())?;
+        let yml = self.after_step(&context(adapters::PipelineStep::Aggregate), yml)?;
+
+        let yml = self.before_step(&context(adapters::PipelineStep::Mixins), yml)?;
+        let mixins = aggregator.mixins;
+        let yml = mixins.inject_with(&yml, options.merge)?;
+        let yml = self.after_step(&context(adapters::PipelineStep::Mixins), yml)?;
+
+        let yml = self.before_step(&context(adapters::PipelineStep::Transform), yml)?;
+        let (yml, traces) = match options.evaluation {
+            EvaluationMode::Evaluate => {
+                let mut list = TransformableList::try_from(yml)?;
+                if self.trace_output.is_some() {
+                    list.trace(aggregator.transform_origins);
+                }
+                list.transform()?;
+                let traces = list.take_traces();
+                (list.try_into()?, traces)
             }
-            None => None,
+            EvaluationMode::Skip => (yml, vec![]),
         };
+        let yml = self.after_step(&context(adapters::PipelineStep::Transform), yml)?;
+
+        let yml = self.before_step(&context(adapters::PipelineStep::Validate), yml)?;
+        if let Some(schema_json) = schema_json {
+            let yml_json_representation = serde_json::to_value(&yml).map_err(AppError::other)?;
+            let validator = JSONSchema::compile(schema_json)
+                .map_err(|e| AppError::ValidateYml(format!("Schema is not valid: {}", e)))?;
+            validator.validate(&yml_json_representation).map_err(|e| {
+                let str_errors = e
+                    .into_iter()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<String>>()
+                    .join("\n");
 
+                AppError::ValidateYml(format!("Generated yml is not valid: {}", str_errors))
+            })?;
+        }
+        let yml = self.after_step(&context(adapters::PipelineStep::Validate), yml)?;
+
+        Ok((yml, traces, aggregator.warnings))
+    }
+
+    fn before_step(
+        &self,
+        context: &adapters::StageContext,
+        yml: serde_yaml::Value,
+    ) -> AppResult<serde_yaml::Value> {
+        self.stages
+            .iter()
+            .try_fold(yml, |yml, stage| stage.before(context, yml))
+    }
+
+    fn after_step(
+        &self,
+        context: &adapters::StageContext,
+        yml: serde_yaml::Value,
+    ) -> AppResult<serde_yaml::Value> {
+        self.stages
+            .iter()
+            .try_fold(yml, |yml, stage| stage.after(context, yml))
+    }
+
+    fn output(
+        &self,
+        yml_id: &str,
+        yml: serde_yaml::Value,
+        schema_id: Option<&str>,
+        schema_json: Option<serde_json::Value>,
+        traces: &[adapters::TransformTrace],
+        format: &AssemblyOutputFormat,
+    ) -> AppResult<()> {
         self.assembly_output
             .output(yml, &PathBuf::from(yml_id), format)?;
 
-        if let Some(schema_json) = schema_json {
+        if let Some(trace_output) = &self.trace_output {
+            trace_output.output(traces, &PathBuf::from(yml_id))?;
+        }
+
+        if let (Some(schema_id), Some(schema_json)) = (schema_id, schema_json) {
             self.schema_output
-                .output(&schema_json, &PathBuf::from(schema_id.unwrap()))?;
+                .output(&schema_json, &PathBuf::from(schema_id))?;
         }
 
         Ok(())
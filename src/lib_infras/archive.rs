@@ -0,0 +1,124 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::Read,
+    path::{Component, Path},
+};
+
+use crate::utils::result::{AppError, AppResult};
+
+enum ArchiveKind {
+    Tar,
+    TarGz,
+    Zip,
+}
+
+fn archive_kind(path: &Path) -> Option<ArchiveKind> {
+    let name = path.file_name()?.to_str()?.to_lowercase();
+    if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(ArchiveKind::TarGz)
+    } else if name.ends_with(".tar") {
+        Some(ArchiveKind::Tar)
+    } else if name.ends_with(".zip") {
+        Some(ArchiveKind::Zip)
+    } else {
+        None
+    }
+}
+
+/// Whether `path` names a `.tar`, `.tar.gz`, `.tgz` or `.zip` bundle.
+pub fn is_archive(path: &Path) -> bool {
+    archive_kind(path).is_some()
+}
+
+/// Reads every file of the archive, keyed by its path inside the archive.
+pub(crate) fn read_archive(path: &Path) -> AppResult<HashMap<String, Vec<u8>>> {
+    let kind = archive_kind(path).ok_or_else(|| {
+        AppError::FileSystem(format!("{} is not a tar or zip archive", path.display()))
+    })?;
+    let file = File::open(path).map_err(|e| {
+        AppError::FileSystem(format!("Could not open archive {}: {e}", path.display()))
+    })?;
+
+    match kind {
+        ArchiveKind::Tar => read_tar(file),
+        ArchiveKind::TarGz => read_tar(flate2::read::GzDecoder::new(file)),
+        ArchiveKind::Zip => read_zip(file),
+    }
+}
+
+/// Name of `file` inside the archive at `archive`, `..` being resolved.
+/// Nothing when it leads out of the archive and `allow_escape` is set, an error otherwise.
+pub(crate) fn confined_entry_name(
+    archive: &Path,
+    file: &str,
+    allow_escape: bool,
+) -> AppResult<Option<String>> {
+    let mut segments: Vec<&str> = vec![];
+    for component in Path::new(file).components() {
+        match component {
+            Component::Normal(segment) => segments.push(segment.to_str().unwrap_or_default()),
+            Component::CurDir => {}
+            Component::ParentDir if segments.pop().is_some() => {}
+            _ if allow_escape => return Ok(None),
+            _ => Err(AppError::ForbiddenPath(format!(
+                "{file} leads out of {}",
+                archive.display()
+            )))?,
+        }
+    }
+    Ok(Some(segments.join("/")))
+}
+
+fn read_tar<R: Read>(reader: R) -> AppResult<HashMap<String, Vec<u8>>> {
+    let mut archive = tar::Archive::new(reader);
+    let mut files = HashMap::new();
+
+    for entry in archive.entries().map_err(AppError::other)? {
+        let mut entry = entry.map_err(AppError::other)?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let Some(name) = entry_name(&entry.path().map_err(AppError::other)?) else {
+            continue;
+        };
+        let mut content = vec![];
+        entry.read_to_end(&mut content).map_err(AppError::other)?;
+        files.insert(name, content);
+    }
+
+    Ok(files)
+}
+
+fn read_zip(file: File) -> AppResult<HashMap<String, Vec<u8>>> {
+    let mut archive = zip::ZipArchive::new(file).map_err(AppError::other)?;
+    let mut files = HashMap::new();
+
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(AppError::other)?;
+        if !entry.is_file() {
+            continue;
+        }
+        let Some(name) = entry.enclosed_name().and_then(entry_name) else {
+            continue;
+        };
+        let mut content = vec![];
+        entry.read_to_end(&mut content).map_err(AppError::other)?;
+        files.insert(name, content);
+    }
+
+    Ok(files)
+}
+
+/// `a/b.pyml` for `./a/b.pyml`, nothing for entries leading out of the archive.
+fn entry_name(path: &Path) -> Option<String> {
+    let segments = path
+        .components()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .map(|component| match component {
+            Component::Normal(segment) => segment.to_str(),
+            _ => None,
+        })
+        .collect::<Option<Vec<&str>>>()?;
+    Some(segments.join("/"))
+}
@@ -37,7 +37,7 @@ impl AssemblyOutputPort for AssemblyFSOutput {
         })?;
 
         if !outfile_parent.exists() {
-            std::fs::create_dir_all(&outfile_parent).map_err(|e| {
+            std::fs::create_dir_all(outfile_parent).map_err(|e| {
                 anyhow::anyhow!(format!("Could not create output directory: {}", e))
             })?;
         }
@@ -13,6 +13,12 @@ pub struct AssemblyIMOutput {
     pub value_yml: Arc<RwLock<HashMap<String, serde_yaml::Value>>>,
     pub value_json: Arc<RwLock<HashMap<String, serde_json::Value>>>,
 }
+impl Default for AssemblyIMOutput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl AssemblyIMOutput {
     pub fn new() -> Self {
         AssemblyIMOutput {
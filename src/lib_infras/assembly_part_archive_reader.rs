@@ -0,0 +1,137 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use glob::{MatchOptions, Pattern};
+
+use super::{
+    archive::{confined_entry_name, read_archive},
+    assembly_part_fs_reader::PartFSReader,
+};
+use crate::{
+    adapters::PartReaderPort,
+    utils::result::{AppError, AppResult},
+};
+
+/// Reads parts from a `.tar`, `.tar.gz` or `.zip` bundle, loaded in memory once.
+pub struct PartArchiveReader {
+    path: PathBuf,
+    files: HashMap<String, Vec<u8>>,
+    extensions: Vec<String>,
+    allow_escape: bool,
+}
+impl PartArchiveReader {
+    pub fn open(path: &Path) -> AppResult<Self> {
+        Ok(PartArchiveReader {
+            path: path.to_path_buf(),
+            files: read_archive(path)?,
+            extensions: vec!["pyml".to_string()],
+            allow_escape: false,
+        })
+    }
+
+    /// Extensions of part files, by precedence when the archive holds several files for one
+    /// identifier. `json` parts are read as json, any other extension as yaml.
+    pub fn with_extensions(mut self, extensions: Vec<String>) -> Self {
+        self.extensions = extensions
+            .into_iter()
+            .map(|ext| ext.trim_start_matches('.').to_string())
+            .collect();
+        self
+    }
+
+    /// Lets parts and resources leading out of the archive be read from the directory
+    /// holding it.
+    pub fn allow_outside_roots(mut self) -> Self {
+        self.allow_escape = true;
+        self
+    }
+
+    fn entry_name(&self, file: &str) -> AppResult<Option<String>> {
+        confined_entry_name(&self.path, file, self.allow_escape)
+    }
+
+    /// Reader of the files next to the archive, for the ones leading out of it.
+    fn outside_reader(&self) -> PartFSReader {
+        let dir = self.path.parent().unwrap_or(Path::new("")).to_path_buf();
+        PartFSReader::new(dir)
+            .with_extensions(self.extensions.clone())
+            .allow_outside_roots()
+    }
+
+    fn read_text(&self, name: &str, content: &[u8]) -> AppResult<String> {
+        String::from_utf8(content.to_vec()).map_err(|_| {
+            AppError::FileSystem(format!(
+                "{name} in {} is not valid UTF-8",
+                self.path.display()
+            ))
+        })
+    }
+}
+impl PartReaderPort for PartArchiveReader {
+    fn get_filepathes_from_glob(&self, glob_str: &str) -> AppResult<Vec<String>> {
+        let Some(glob_str) = self.entry_name(glob_str)? else {
+            return self.outside_reader().get_filepathes_from_glob(glob_str);
+        };
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..MatchOptions::new()
+        };
+
+        let mut filepathes = vec![];
+        for extension in self.extensions.iter() {
+            let pattern =
+                Pattern::new(&format!("{glob_str}.{extension}")).map_err(AppError::other)?;
+            for name in self.files.keys() {
+                let identifier = match name.strip_suffix(&format!(".{extension}")) {
+                    Some(identifier) if pattern.matches_with(name, options) => identifier,
+                    _ => continue,
+                };
+                if !filepathes.iter().any(|path| path == identifier) {
+                    filepathes.push(identifier.to_string());
+                }
+            }
+        }
+        filepathes.sort();
+
+        Ok(filepathes)
+    }
+
+    fn get_value(&self, identifier: &str) -> AppResult<serde_yaml::Value> {
+        let Some(name) = self.entry_name(identifier)? else {
+            return self.outside_reader().get_value(identifier);
+        };
+
+        for extension in self.extensions.iter() {
+            let file = format!("{name}.{extension}");
+            let Some(content) = self.files.get(&file) else {
+                continue;
+            };
+            let content = self.read_text(&file, content)?;
+            let parse_error =
+                |e: String| AppError::ParseYml(format!("Could not parse {file}: {e}"));
+            return match extension.as_str() {
+                "json" => {
+                    let json: serde_json::Value =
+                        serde_json::from_str(&content).map_err(|e| parse_error(e.to_string()))?;
+                    serde_yaml::to_value(json).map_err(AppError::other)
+                }
+                _ => serde_yaml::from_str(&content).map_err(|e| parse_error(e.to_string())),
+            };
+        }
+
+        Err(AppError::MissingPart(identifier.to_string()))
+    }
+
+    fn get_resource(&self, identifier: &str) -> AppResult<String> {
+        let Some(name) = self.entry_name(identifier)? else {
+            return self.outside_reader().get_resource(identifier);
+        };
+        let content = self
+            .files
+            .get(&name)
+            .ok_or_else(|| AppError::FileSystem(format!("Could not find resource {identifier}")))?;
+        self.read_text(&name, content)
+    }
+}
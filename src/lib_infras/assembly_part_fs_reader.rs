@@ -1,38 +1,169 @@
+use super::sandbox::join_confined;
 use crate::{
     adapters::PartReaderPort,
     utils::result::{AppError, AppResult},
 };
 use glob::glob;
-use std::{collections::HashMap, path::PathBuf, sync::RwLock};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::RwLock,
+    time::SystemTime,
+};
 
 pub struct PartFSReader {
-    context: PathBuf,
-    read_cache: RwLock<HashMap<String, serde_yaml::Value>>,
+    roots: Vec<PathBuf>,
+    aliases: HashMap<String, PathBuf>,
+    extensions: Vec<String>,
+    allow_escape: bool,
+    log: bool,
+    /// Parts by identifier, with the version and the checksum of the file they were read from.
+    read_cache: RwLock<HashMap<String, (FileVersion, u64, serde_yaml::Value)>>,
+}
+
+/// A cached part is reused without reading its file as long as this is unchanged.
+#[derive(Debug, PartialEq)]
+struct FileVersion {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    len: u64,
+}
+impl FileVersion {
+    fn of(path: &Path) -> AppResult<Self> {
+        let metadata = std::fs::metadata(path).map_err(AppError::other)?;
+        Ok(FileVersion {
+            path: path.to_path_buf(),
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+        })
+    }
 }
 impl PartFSReader {
     pub fn new(path: PathBuf) -> Self {
+        PartFSReader::with_roots(vec![path])
+    }
+
+    /// Searches parts in several roots, the first root holding an identifier wins.
+    pub fn with_roots(roots: Vec<PathBuf>) -> Self {
         PartFSReader {
-            context: path,
+            roots,
+            aliases: HashMap::new(),
+            extensions: vec!["pyml".to_string()],
+            allow_escape: false,
+            log: false,
             read_cache: RwLock::new(HashMap::new()),
         }
     }
+
+    /// Makes `@name/tags/adult` point to `tags/adult` in the `path` directory.
+    pub fn with_alias(mut self, name: &str, path: PathBuf) -> Self {
+        let name = name.strip_prefix('@').unwrap_or(name);
+        self.aliases.insert(name.to_string(), path);
+        self
+    }
+
+    /// Extensions of part files, by precedence when a root holds several files for one identifier.
+    /// `json` parts are read as json, any other extension as yaml.
+    pub fn with_extensions(mut self, extensions: Vec<String>) -> Self {
+        self.extensions = extensions
+            .into_iter()
+            .map(|ext| ext.trim_start_matches('.').to_string())
+            .collect();
+        self
+    }
+
+    /// Prints the parts read, and the ones reused from the cache.
+    pub fn with_log(mut self, log: bool) -> Self {
+        self.log = log;
+        self
+    }
+
+    /// Forgets the cached value of a part, it is read again on next use.
+    pub fn invalidate(&self, identifier: &str) -> AppResult<()> {
+        let mut cache = self
+            .read_cache
+            .write()
+            .map_err(|e| AppError::FileSystem(format!("Could not write to cache: {}", e)))?;
+        cache.remove(identifier);
+        Ok(())
+    }
+
+    pub fn clear(&self) -> AppResult<()> {
+        let mut cache = self
+            .read_cache
+            .write()
+            .map_err(|e| AppError::FileSystem(format!("Could not write to cache: {}", e)))?;
+        cache.clear();
+        Ok(())
+    }
+
+    /// Lets parts and resources be read outside of the roots and aliased directories.
+    pub fn allow_outside_roots(mut self) -> Self {
+        self.allow_escape = true;
+        self
+    }
+
+    /// Directories to look `file` up in, with `file` relative to them and the alias prefix to give back.
+    fn search_dirs<'a>(&'a self, file: &'a str) -> AppResult<(Vec<&'a PathBuf>, &'a str, String)> {
+        let Some(aliased) = file.strip_prefix('@') else {
+            return Ok((self.roots.iter().collect(), file, String::new()));
+        };
+
+        let (name, file) = aliased.split_once('/').unwrap_or((aliased, ""));
+        let dir = self
+            .aliases
+            .get(name)
+            .ok_or_else(|| AppError::FileSystem(format!("Unknown alias @{name} in {aliased}")))?;
+
+        Ok((vec![dir], file, format!("@{name}/")))
+    }
+
+    /// First existing file among `file` with each of `extensions`, or `file` itself without extensions.
+    fn find(&self, file: &str, extensions: &[String]) -> AppResult<Option<PathBuf>> {
+        let (dirs, file, _) = self.search_dirs(file)?;
+        let candidates = match extensions.is_empty() {
+            true => vec![file.to_string()],
+            false => extensions
+                .iter()
+                .map(|ext| format!("{file}.{ext}"))
+                .collect(),
+        };
+
+        for dir in dirs {
+            for candidate in candidates.iter() {
+                let path = join_confined(dir, candidate, self.allow_escape)?;
+                if path.is_file() {
+                    return Ok(Some(path));
+                }
+            }
+        }
+        Ok(None)
+    }
 }
 impl PartReaderPort for PartFSReader {
     fn get_filepathes_from_glob(&self, glob_str: &str) -> AppResult<Vec<String>> {
-        let absolute_context = self.context.canonicalize().map_err(AppError::other)?;
-        let glob_path = absolute_context.join(glob_str).with_extension("pyml");
-        let glob_path = glob_path
-            .to_str()
-            .ok_or_else(|| AppError::FileSystem("Could not convert path to str".to_string()))?;
-
-        let filepathes = glob(glob_path).map_err(AppError::other)?;
-        let filepathes = filepathes
-            .map(|filepath| filepath.map_err(AppError::other))
-            .collect::<Result<Vec<PathBuf>, AppError>>()?;
-
-        let filepathes = filepathes
-            .iter()
-            .map(|filepath| {
+        let mut filepathes: Vec<String> = vec![];
+        let (dirs, glob_str, prefix) = self.search_dirs(glob_str)?;
+
+        for (root, extension) in dirs
+            .into_iter()
+            .flat_map(|root| self.extensions.iter().map(move |ext| (root, ext)))
+        {
+            let absolute_context = root.canonicalize().map_err(|e| {
+                AppError::FileSystem(format!("Could not open root {}: {e}", root.display()))
+            })?;
+            let glob_path = join_confined(&absolute_context, glob_str, self.allow_escape)?
+                .with_extension(extension);
+            let glob_path = glob_path
+                .to_str()
+                .ok_or_else(|| AppError::FileSystem("Could not convert path to str".to_string()))?;
+
+            let root_filepathes = glob(glob_path).map_err(AppError::other)?;
+            let root_filepathes = root_filepathes
+                .map(|filepath| filepath.map_err(AppError::other))
+                .collect::<Result<Vec<PathBuf>, AppError>>()?;
+
+            for filepath in root_filepathes {
                 let path = filepath
                     .strip_prefix(&absolute_context)
                     .map_err(AppError::other)?;
@@ -43,34 +174,156 @@ impl PartReaderPort for PartFSReader {
                     AppError::FileSystem("Could not convert path to str".to_string())
                 })?;
 
-                Ok(path_str.to_string())
-            })
-            .collect::<AppResult<Vec<String>>>()?;
+                let path_str = format!("{prefix}{path_str}");
+                if !filepathes.contains(&path_str) {
+                    filepathes.push(path_str);
+                }
+            }
+        }
 
         Ok(filepathes)
     }
 
     fn get_value(&self, identifier: &str) -> AppResult<serde_yaml::Value> {
-        let mut cache = self.read_cache.write().map_err(|e| {
-            AppError::FileSystem(format!("Could not write to cache: {}", e.to_string()))
-        })?;
-        let cached_value = cache.get(identifier);
-
-        match cached_value {
-            Some(value) => {
-                println!("reading from cache: {}", identifier);
-                Ok(value.clone())
+        let path = self
+            .find(identifier, &self.extensions)?
+            .ok_or_else(|| AppError::MissingPart(identifier.to_string()))?;
+        let version = FileVersion::of(&path)?;
+
+        let cached = {
+            let cache = self
+                .read_cache
+                .read()
+                .map_err(|e| AppError::FileSystem(format!("Could not read cache: {}", e)))?;
+            match cache.get(identifier) {
+                Some((cached_version, _, value)) if *cached_version == version => {
+                    if self.log {
+                        println!("reading from cache: {}", identifier);
+                    }
+                    return Ok(value.clone());
+                }
+                Some((_, checksum, value)) => Some((*checksum, value.clone())),
+                None => None,
             }
-            None => {
-                println!("reading: {}", identifier);
-                let path = self.context.join(format!("{identifier}.pyml"));
-                let file = std::fs::read_to_string(path).map_err(AppError::other)?;
-                let yml: serde_yaml::Value =
-                    serde_yaml::from_str(&file).map_err(AppError::other)?;
-
-                cache.insert(identifier.to_string(), yml.clone());
-                Ok(yml)
+        };
+
+        let file = std::fs::read_to_string(&path).map_err(AppError::other)?;
+        let checksum = xxhash_rust::xxh3::xxh3_64(file.as_bytes());
+        // A file touched without being edited is not parsed again.
+        let yml: serde_yaml::Value = match cached {
+            Some((cached_checksum, value)) if cached_checksum == checksum => value,
+            _ => {
+                if self.log {
+                    println!("reading: {}", identifier);
+                }
+                match path.extension() {
+                    Some(ext) if ext == "json" => {
+                        let json: serde_json::Value =
+                            serde_json::from_str(&file).map_err(AppError::other)?;
+                        serde_yaml::to_value(json).map_err(AppError::other)?
+                    }
+                    _ => serde_yaml::from_str(&file).map_err(AppError::other)?,
+                }
             }
-        }
+        };
+
+        let mut cache = self
+            .read_cache
+            .write()
+            .map_err(|e| AppError::FileSystem(format!("Could not write to cache: {}", e)))?;
+        cache.insert(identifier.to_string(), (version, checksum, yml.clone()));
+        Ok(yml)
+    }
+
+    fn get_resource(&self, identifier: &str) -> AppResult<String> {
+        let path = self
+            .find(identifier, &[])?
+            .ok_or_else(|| AppError::FileSystem(format!("Could not find resource {identifier}")))?;
+        std::fs::read_to_string(path).map_err(AppError::other)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::adapters::PartReaderPort;
+    use std::fs;
+
+    #[test]
+    fn it_should_read_edited_parts_again() {
+        let root = std::env::temp_dir().join(format!("yml_assembler_cache_{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let part = root.join("part.pyml");
+        let reader = super::PartFSReader::new(root.clone());
+        let read = || reader.get_value("part").unwrap()["value"].as_u64().unwrap();
+
+        fs::write(&part, "value: 1").unwrap();
+        assert_eq!(read(), 1);
+
+        fs::write(&part, "value: 22").unwrap();
+        assert_eq!(read(), 22);
+
+        reader.invalidate("part").unwrap();
+        assert_eq!(read(), 22);
+        reader.clear().unwrap();
+        assert_eq!(read(), 22);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn it_should_not_read_parts_whose_file_metadata_is_unchanged() {
+        let root = std::env::temp_dir().join(format!("yml_assembler_edit_{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let part = root.join("part.pyml");
+        let reader = super::PartFSReader::new(root.clone());
+
+        fs::write(&part, "value: 1").unwrap();
+        let modified = fs::metadata(&part).unwrap().modified().unwrap();
+        assert_eq!(reader.get_value("part").unwrap()["value"], 1);
+
+        fs::write(&part, "value: [").unwrap();
+        fs::File::options()
+            .write(true)
+            .open(&part)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        assert_eq!(reader.get_value("part").unwrap()["value"], 1);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn it_should_forget_invalidated_and_cleared_parts() {
+        let root =
+            std::env::temp_dir().join(format!("yml_assembler_forget_{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("a.pyml"), "value: 1").unwrap();
+        fs::write(root.join("b.pyml"), "value: 2").unwrap();
+        let reader = super::PartFSReader::new(root.clone());
+        let cached = || {
+            let mut cached = reader
+                .read_cache
+                .read()
+                .unwrap()
+                .keys()
+                .cloned()
+                .collect::<Vec<String>>();
+            cached.sort();
+            cached
+        };
+
+        reader.get_value("a").unwrap();
+        reader.get_value("b").unwrap();
+        assert_eq!(cached(), vec!["a", "b"]);
+
+        reader.invalidate("a").unwrap();
+        assert_eq!(cached(), vec!["b"]);
+
+        reader.get_value("a").unwrap();
+        reader.clear().unwrap();
+        assert!(cached().is_empty());
+
+        fs::remove_dir_all(root).unwrap();
     }
 }
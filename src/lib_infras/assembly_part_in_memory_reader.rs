@@ -0,0 +1,144 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+use glob::{MatchOptions, Pattern};
+
+use crate::{
+    adapters::PartReaderPort,
+    utils::result::{AppError, AppResult},
+};
+
+pub struct PartIMReader {
+    pub parts: Arc<RwLock<HashMap<String, serde_yaml::Value>>>,
+    pub resources: Arc<RwLock<HashMap<String, String>>>,
+}
+impl Default for PartIMReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartIMReader {
+    pub fn new() -> Self {
+        PartIMReader::from_values(HashMap::new())
+    }
+
+    /// Parts keyed by identifier, like `chapters/intro`.
+    pub fn from_values(parts: HashMap<String, serde_yaml::Value>) -> Self {
+        PartIMReader {
+            parts: Arc::new(RwLock::new(parts)),
+            resources: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    /// Parts keyed by identifier, given as pyml content.
+    pub fn from_strings(parts: HashMap<String, String>) -> AppResult<Self> {
+        let parts = parts
+            .into_iter()
+            .map(|(identifier, content)| {
+                let value = serde_yaml::from_str(&content).map_err(|e| {
+                    AppError::ParseYml(format!("Could not parse {identifier}: {e}"))
+                })?;
+                Ok((identifier, value))
+            })
+            .collect::<AppResult<HashMap<String, serde_yaml::Value>>>()?;
+        Ok(PartIMReader::from_values(parts))
+    }
+
+    pub fn insert_part(&self, identifier: &str, value: serde_yaml::Value) -> AppResult<()> {
+        let mut parts = self
+            .parts
+            .write()
+            .map_err(|_| AppError::FileSystem("Cannot write parts".to_string()))?;
+        parts.insert(identifier.to_string(), value);
+        Ok(())
+    }
+
+    /// Content given to `!text::`, `!json::`, `!yaml::` and `!csv::`, keyed with its extension.
+    pub fn insert_resource(&self, identifier: &str, content: &str) -> AppResult<()> {
+        let mut resources = self
+            .resources
+            .write()
+            .map_err(|_| AppError::FileSystem("Cannot write resources".to_string()))?;
+        resources.insert(identifier.to_string(), content.to_string());
+        Ok(())
+    }
+}
+impl PartReaderPort for PartIMReader {
+    fn get_filepathes_from_glob(&self, glob_str: &str) -> AppResult<Vec<String>> {
+        let pattern = Pattern::new(glob_str).map_err(AppError::other)?;
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..MatchOptions::new()
+        };
+        let parts = self
+            .parts
+            .read()
+            .map_err(|_| AppError::FileSystem("Cannot read parts".to_string()))?;
+
+        let mut filepathes = parts
+            .keys()
+            .filter(|identifier| pattern.matches_with(identifier, options))
+            .cloned()
+            .collect::<Vec<String>>();
+        filepathes.sort();
+
+        Ok(filepathes)
+    }
+
+    fn get_value(&self, identifier: &str) -> AppResult<serde_yaml::Value> {
+        let parts = self
+            .parts
+            .read()
+            .map_err(|_| AppError::FileSystem("Cannot read parts".to_string()))?;
+        parts
+            .get(identifier)
+            .cloned()
+            .ok_or_else(|| AppError::MissingPart(identifier.to_string()))
+    }
+
+    fn get_resource(&self, identifier: &str) -> AppResult<String> {
+        let resources = self
+            .resources
+            .read()
+            .map_err(|_| AppError::FileSystem("Cannot read resources".to_string()))?;
+        resources
+            .get(identifier)
+            .cloned()
+            .ok_or_else(|| AppError::FileSystem(format!("Could not find resource {identifier}")))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use crate::{adapters::PartReaderPort, utils::result::AppError};
+
+    #[test]
+    fn it_should_read_parts_and_match_globs() {
+        let reader = super::PartIMReader::from_strings(HashMap::from([
+            ("book".to_string(), "title: Book".to_string()),
+            ("chapters/one".to_string(), "number: 1".to_string()),
+            ("chapters/two".to_string(), "number: 2".to_string()),
+            ("chapters/drafts/three".to_string(), "number: 3".to_string()),
+        ]))
+        .unwrap();
+
+        let book = reader.get_value("book").unwrap();
+        assert_eq!(
+            book,
+            serde_yaml::from_str::<serde_yaml::Value>("title: Book").unwrap()
+        );
+
+        let chapters = reader.get_filepathes_from_glob("chapters/*").unwrap();
+        assert_eq!(chapters, vec!["chapters/one", "chapters/two"]);
+
+        assert!(matches!(
+            reader.get_value("missing"),
+            Err(AppError::MissingPart(_))
+        ));
+    }
+}
@@ -0,0 +1,66 @@
+use std::path::PathBuf;
+
+use crate::{
+    adapters::{BuildCachePort, BuildRecord},
+    utils::result::{AppError, AppResult},
+};
+
+/// Keeps one `<entry>.cache.yml` record per entry in a cache directory.
+pub struct BuildCacheFS {
+    context: PathBuf,
+    log: bool,
+}
+
+impl BuildCacheFS {
+    pub fn new(path: PathBuf) -> Self {
+        BuildCacheFS {
+            context: path,
+            log: false,
+        }
+    }
+
+    /// Prints the records ignored because they could not be read.
+    pub fn with_log(mut self, log: bool) -> Self {
+        self.log = log;
+        self
+    }
+
+    fn record_path(&self, entry: &str) -> PathBuf {
+        self.context.join(format!("{entry}.cache.yml"))
+    }
+}
+
+impl BuildCachePort for BuildCacheFS {
+    fn get(&self, entry: &str) -> AppResult<Option<BuildRecord>> {
+        let record_path = self.record_path(entry);
+        if !record_path.is_file() {
+            return Ok(None);
+        }
+
+        let record = std::fs::read_to_string(&record_path).map_err(AppError::other)?;
+        match serde_yaml::from_str(&record) {
+            Ok(record) => Ok(Some(record)),
+            Err(e) => {
+                if self.log {
+                    println!("ignoring unreadable cache {}: {e}", record_path.display());
+                }
+                Ok(None)
+            }
+        }
+    }
+
+    fn set(&self, entry: &str, record: &BuildRecord) -> AppResult<()> {
+        let record_path = self.record_path(entry);
+        if let Some(parent) = record_path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| anyhow::anyhow!(format!("Could not create cache directory: {}", e)))?;
+        }
+
+        let record = serde_yaml::to_string(record)
+            .map_err(|e| anyhow::anyhow!(format!("Could not serialize cache: {}", e)))?;
+        std::fs::write(record_path, record)
+            .map_err(|e| anyhow::anyhow!(format!("Could not write cache: {}", e)))?;
+
+        Ok(())
+    }
+}
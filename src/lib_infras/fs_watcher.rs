@@ -0,0 +1,170 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::utils::result::{AppError, AppResult};
+
+/// Polls directories for created, modified and deleted files.
+pub struct FSWatcher {
+    dirs: Vec<(PathBuf, String)>,
+    excluded: Vec<PathBuf>,
+    modified: HashMap<String, Option<SystemTime>>,
+}
+
+impl FSWatcher {
+    /// Watches each directory, naming its files with the given prefix, like `@common/`.
+    /// The `excluded` directories, like the output one, are skipped as are symlinks.
+    pub fn new(dirs: Vec<(PathBuf, String)>, excluded: Vec<PathBuf>) -> AppResult<Self> {
+        let mut watcher = FSWatcher {
+            dirs,
+            excluded,
+            modified: HashMap::new(),
+        };
+        watcher.modified = watcher.scan()?;
+        Ok(watcher)
+    }
+
+    /// Files changed since the previous call, relative to their directory like `chapters/intro.pyml`.
+    pub fn changes(&mut self) -> AppResult<Vec<String>> {
+        let modified = self.scan()?;
+
+        let mut changes = modified
+            .iter()
+            .filter(|(file, time)| self.modified.get(*file) != Some(time))
+            .chain(
+                self.modified
+                    .iter()
+                    .filter(|(file, _)| !modified.contains_key(*file)),
+            )
+            .map(|(file, _)| file.clone())
+            .collect::<Vec<String>>();
+        changes.sort();
+        changes.dedup();
+
+        self.modified = modified;
+        Ok(changes)
+    }
+
+    fn scan(&self) -> AppResult<HashMap<String, Option<SystemTime>>> {
+        // Canonicalized on each scan, an excluded directory may be created later on.
+        let excluded = self
+            .excluded
+            .iter()
+            .map(|dir| dir.canonicalize().unwrap_or(dir.clone()))
+            .collect::<Vec<_>>();
+        let mut modified = HashMap::new();
+        for (dir, prefix) in self.dirs.iter() {
+            scan_dir(dir, dir, prefix, &excluded, &mut modified)?;
+        }
+        Ok(modified)
+    }
+}
+
+fn scan_dir(
+    root: &Path,
+    dir: &Path,
+    prefix: &str,
+    excluded: &[PathBuf],
+    modified: &mut HashMap<String, Option<SystemTime>>,
+) -> AppResult<()> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| AppError::FileSystem(format!("Could not watch {}: {e}", dir.display())))?;
+
+    for entry in entries {
+        let entry = entry.map_err(AppError::other)?;
+        let path = entry.path();
+        let file_type = entry.file_type().map_err(AppError::other)?;
+        if file_type.is_symlink() {
+            continue;
+        }
+        if file_type.is_dir() {
+            let canonical = path.canonicalize().unwrap_or(path.clone());
+            if !excluded.contains(&canonical) {
+                scan_dir(root, &path, prefix, excluded, modified)?;
+            }
+            continue;
+        }
+
+        let Ok(relative) = path.strip_prefix(root) else {
+            continue;
+        };
+        let relative = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        let time = path
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        modified
+            .entry(format!("{prefix}{relative}"))
+            .or_insert(time);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    #[test]
+    fn it_should_list_created_modified_and_deleted_files() {
+        let root = std::env::temp_dir().join(format!("yml_assembler_watch_{}", std::process::id()));
+        fs::create_dir_all(root.join("tags")).unwrap();
+        fs::write(root.join("kept.pyml"), "a: 1").unwrap();
+        fs::write(root.join("deleted.pyml"), "a: 1").unwrap();
+        fs::write(root.join("tags/edited.pyml"), "a: 1").unwrap();
+
+        let mut watcher =
+            super::FSWatcher::new(vec![(root.clone(), "@lib/".to_string())], vec![]).unwrap();
+        assert!(watcher.changes().unwrap().is_empty());
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        fs::remove_file(root.join("deleted.pyml")).unwrap();
+        fs::write(root.join("tags/edited.pyml"), "a: 2").unwrap();
+        fs::write(root.join("created.pyml"), "a: 1").unwrap();
+
+        assert_eq!(
+            watcher.changes().unwrap(),
+            vec![
+                "@lib/created.pyml",
+                "@lib/deleted.pyml",
+                "@lib/tags/edited.pyml"
+            ]
+        );
+        assert!(watcher.changes().unwrap().is_empty());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_should_skip_symlinks_and_excluded_directories() {
+        let root = std::env::temp_dir().join(format!(
+            "yml_assembler_watch_excluded_{}",
+            std::process::id()
+        ));
+        fs::create_dir_all(root.join("tags")).unwrap();
+        fs::write(root.join("tags/kept.pyml"), "a: 1").unwrap();
+        std::os::unix::fs::symlink(&root, root.join("tags/loop")).unwrap();
+
+        let mut watcher = super::FSWatcher::new(
+            vec![(root.clone(), "".to_string())],
+            vec![root.join("output")],
+        )
+        .unwrap();
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        fs::create_dir_all(root.join("output")).unwrap();
+        fs::write(root.join("output/book.yml"), "a: 1").unwrap();
+        fs::write(root.join("tags/kept.pyml"), "a: 2").unwrap();
+
+        assert_eq!(watcher.changes().unwrap(), vec!["tags/kept.pyml"]);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -1,6 +1,15 @@
+pub mod archive;
 pub mod assembly_fs_output;
 pub mod assembly_in_memory_output;
+pub mod assembly_part_archive_reader;
 pub mod assembly_part_fs_reader;
+pub mod assembly_part_in_memory_reader;
+pub mod build_cache_fs;
+pub mod fs_watcher;
+mod sandbox;
+pub mod schema_archive_reader;
 pub mod schema_fs_output;
 pub mod schema_fs_reader;
 pub mod schema_in_memory_output;
+pub mod trace_fs_output;
+pub mod trace_in_memory_output;
@@ -0,0 +1,61 @@
+use crate::utils::result::{AppError, AppResult};
+use std::path::{Component, Path, PathBuf};
+
+/// Joins `file` onto `dir`, refusing absolute paths and paths leading out of `dir`,
+/// either through `..` or through a symlink, unless `allow_escape` is set.
+pub(crate) fn join_confined(dir: &Path, file: &str, allow_escape: bool) -> AppResult<PathBuf> {
+    if allow_escape {
+        return Ok(dir.join(file));
+    }
+
+    let forbidden = || AppError::ForbiddenPath(format!("{file} leads out of {}", dir.display()));
+    let mut normalized = PathBuf::new();
+    for component in Path::new(file).components() {
+        match component {
+            Component::Normal(segment) => normalized.push(segment),
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    return Err(forbidden());
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => return Err(forbidden()),
+        }
+    }
+
+    let path = dir.join(normalized);
+    if let (Ok(canonical_dir), Ok(canonical_path)) = (dir.canonicalize(), path.canonicalize()) {
+        if !canonical_path.starts_with(canonical_dir) {
+            return Err(forbidden());
+        }
+    }
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_should_confine_paths() {
+        let dir = Path::new("root");
+
+        assert_eq!(
+            join_confined(dir, "a/../b/./c", false).unwrap(),
+            PathBuf::from("root/b/c")
+        );
+        assert!(matches!(
+            join_confined(dir, "a/../../secret", false),
+            Err(AppError::ForbiddenPath(_))
+        ));
+        assert!(matches!(
+            join_confined(dir, "/etc/secret", false),
+            Err(AppError::ForbiddenPath(_))
+        ));
+        assert_eq!(
+            join_confined(dir, "../secret", true).unwrap(),
+            PathBuf::from("root/../secret")
+        );
+    }
+}
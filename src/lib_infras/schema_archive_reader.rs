@@ -0,0 +1,89 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use super::{
+    archive::{confined_entry_name, read_archive},
+    schema_fs_reader::SchemaFSReader,
+};
+use crate::{
+    adapters::SchemaReaderPort,
+    utils::result::{AppError, AppResult},
+};
+
+/// Reads schemas from a `.tar`, `.tar.gz` or `.zip` bundle, loaded in memory once.
+pub struct SchemaArchiveReader {
+    path: PathBuf,
+    files: HashMap<String, Vec<u8>>,
+    allow_escape: bool,
+    log: bool,
+}
+impl SchemaArchiveReader {
+    pub fn open(path: &Path) -> AppResult<Self> {
+        Ok(SchemaArchiveReader {
+            path: path.to_path_buf(),
+            files: read_archive(path)?,
+            allow_escape: false,
+            log: false,
+        })
+    }
+
+    /// Lets schemas leading out of the archive be read from the directory holding it.
+    pub fn allow_outside_roots(mut self) -> Self {
+        self.allow_escape = true;
+        self
+    }
+
+    /// Prints the schemas loaded.
+    pub fn with_log(mut self, log: bool) -> Self {
+        self.log = log;
+        self
+    }
+
+    fn get_file(&self, path: &Path) -> AppResult<&[u8]> {
+        let name = path.to_str().ok_or_else(|| {
+            AppError::FileSystem(format!(
+                "{path:?} filename probably contains invalid characters"
+            ))
+        })?;
+        self.files
+            .get(name)
+            .map(|content| content.as_slice())
+            .ok_or_else(|| AppError::FileSystem(format!("Could not find schema {name}")))
+    }
+}
+impl SchemaReaderPort for SchemaArchiveReader {
+    fn get_validation_schema(&self, path_str: &str) -> AppResult<serde_json::Value> {
+        let Some(name) = confined_entry_name(&self.path, path_str, self.allow_escape)? else {
+            let dir = self.path.parent().unwrap_or(Path::new("")).to_path_buf();
+            return SchemaFSReader::new(dir)
+                .allow_outside_roots()
+                .with_log(self.log)
+                .get_validation_schema(path_str);
+        };
+
+        let path = PathBuf::from(name);
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("json") => self.get_schema_from_json(&path),
+            Some("yml") | Some("yaml") => self.get_schema_from_yml(&path),
+            _ => Err(AppError::FileSystem(format!(
+                "{path_str} has an invalid extension, load either a json, yml or yaml file"
+            ))),
+        }
+    }
+
+    fn get_schema_from_json(&self, path: &PathBuf) -> AppResult<serde_json::Value> {
+        if self.log {
+            println!("loading json schema: {:?}", path);
+        }
+        serde_json::from_slice(self.get_file(path)?).map_err(AppError::other)
+    }
+
+    fn get_schema_from_yml(&self, path: &PathBuf) -> AppResult<serde_json::Value> {
+        if self.log {
+            println!("loading yml schema: {:?}", path);
+        }
+        serde_yaml::from_slice(self.get_file(path)?).map_err(AppError::other)
+    }
+}
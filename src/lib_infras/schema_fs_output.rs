@@ -15,7 +15,7 @@ impl SchemaFSOutput {
 impl SchemaOutputPort for SchemaFSOutput {
     fn output(&self, value: &serde_json::Value, schema_path: &PathBuf) -> AppResult<()> {
         let outschema_path = PathBuf::from(&self.context)
-            .join(&schema_path)
+            .join(schema_path)
             .with_extension("json");
 
         let outschema_parent = outschema_path.parent().ok_or_else(|| {
@@ -26,7 +26,7 @@ impl SchemaOutputPort for SchemaFSOutput {
         })?;
 
         if !outschema_parent.exists() {
-            std::fs::create_dir_all(&outschema_parent).map_err(|e| {
+            std::fs::create_dir_all(outschema_parent).map_err(|e| {
                 anyhow::anyhow!(format!("Could not create output directory: {}", e))
             })?;
         }
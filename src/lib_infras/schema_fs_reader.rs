@@ -1,21 +1,52 @@
 use std::path::PathBuf;
 
+use super::sandbox::join_confined;
 use crate::{
     adapters::SchemaReaderPort,
     utils::result::{AppError, AppResult},
 };
 
 pub struct SchemaFSReader {
-    context: PathBuf,
+    roots: Vec<PathBuf>,
+    allow_escape: bool,
+    log: bool,
 }
 impl SchemaFSReader {
     pub fn new(path: PathBuf) -> Self {
-        SchemaFSReader { context: path }
+        SchemaFSReader::with_roots(vec![path])
+    }
+
+    /// Searches schemas in several roots, the first root holding the schema wins.
+    pub fn with_roots(roots: Vec<PathBuf>) -> Self {
+        SchemaFSReader {
+            roots,
+            allow_escape: false,
+            log: false,
+        }
+    }
+
+    /// Lets schemas be read outside of the roots.
+    pub fn allow_outside_roots(mut self) -> Self {
+        self.allow_escape = true;
+        self
+    }
+
+    /// Prints the schemas loaded.
+    pub fn with_log(mut self, log: bool) -> Self {
+        self.log = log;
+        self
     }
 }
 impl SchemaReaderPort for SchemaFSReader {
     fn get_validation_schema(&self, path_str: &str) -> AppResult<serde_json::Value> {
-        let path = self.context.join(format!("{path_str}"));
+        let path = self
+            .roots
+            .iter()
+            .map(|root| join_confined(root, path_str, self.allow_escape))
+            .collect::<AppResult<Vec<PathBuf>>>()?
+            .into_iter()
+            .find(|path| path.is_file())
+            .ok_or_else(|| AppError::FileSystem(format!("Could not find schema {path_str}")))?;
         let extension = path
             .extension()
             .ok_or_else(|| {
@@ -45,14 +76,18 @@ impl SchemaReaderPort for SchemaFSReader {
     }
 
     fn get_schema_from_json(&self, path: &PathBuf) -> AppResult<serde_json::Value> {
-        println!("loading json schema: {:?}", path);
+        if self.log {
+            println!("loading json schema: {:?}", path);
+        }
         let file = std::fs::File::open(path).map_err(AppError::other)?;
         let schema: serde_json::Value = serde_json::from_reader(file).map_err(AppError::other)?;
         Ok(schema)
     }
 
     fn get_schema_from_yml(&self, path: &PathBuf) -> AppResult<serde_json::Value> {
-        println!("loading yml schema: {:?}", path);
+        if self.log {
+            println!("loading yml schema: {:?}", path);
+        }
         let file = std::fs::File::open(path).map_err(AppError::other)?;
         let schema: serde_json::Value = serde_yaml::from_reader(file).map_err(AppError::other)?;
         Ok(schema)
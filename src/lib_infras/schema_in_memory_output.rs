@@ -11,6 +11,12 @@ use crate::{
 pub struct SchemaIMOutput {
     pub value: Arc<RwLock<Option<serde_json::Value>>>,
 }
+impl Default for SchemaIMOutput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SchemaIMOutput {
     pub fn new() -> Self {
         SchemaIMOutput {
@@ -0,0 +1,47 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    adapters::{TraceOutputPort, TransformTrace},
+    utils::result::AppResult,
+};
+
+pub struct TraceFSOutput {
+    context: PathBuf,
+}
+
+impl TraceFSOutput {
+    pub fn new(path: PathBuf) -> Self {
+        TraceFSOutput { context: path }
+    }
+}
+
+impl TraceOutputPort for TraceFSOutput {
+    fn output(&self, traces: &[TransformTrace], file_path: &Path) -> AppResult<()> {
+        let outtrace_path =
+            PathBuf::from(&self.context).join(format!("{}.trace.json", file_path.display()));
+
+        let outtrace_parent = outtrace_path.parent().ok_or_else(|| {
+            anyhow::anyhow!(format!(
+                "Could not get parent directory of {}",
+                outtrace_path.display()
+            ))
+        })?;
+
+        if !outtrace_parent.exists() {
+            std::fs::create_dir_all(outtrace_parent).map_err(|e| {
+                anyhow::anyhow!(format!("Could not create output directory: {}", e))
+            })?;
+        }
+
+        std::fs::write(
+            outtrace_path,
+            serde_json::to_string_pretty(traces)
+                .map_err(|e| anyhow::anyhow!(format!("Could not serialize traces: {}", e)))?,
+        )
+        .map_err(|e| {
+            anyhow::anyhow!(format!("Could not write traces to output directory: {}", e))
+        })?;
+
+        Ok(())
+    }
+}
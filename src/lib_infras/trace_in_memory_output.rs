@@ -0,0 +1,45 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use crate::{
+    adapters::{TraceOutputPort, TransformTrace},
+    utils::result::{AppError, AppResult},
+};
+
+pub struct TraceIMOutput {
+    pub value: Arc<RwLock<HashMap<String, Vec<TransformTrace>>>>,
+}
+impl Default for TraceIMOutput {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TraceIMOutput {
+    pub fn new() -> Self {
+        TraceIMOutput {
+            value: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub fn get_output(&self) -> AppResult<HashMap<String, Vec<TransformTrace>>> {
+        let in_memory_ref = self
+            .value
+            .read()
+            .map_err(|_| AppError::FileSystem("Cannot read trace output".to_string()))?;
+        Ok(in_memory_ref.clone())
+    }
+}
+impl TraceOutputPort for TraceIMOutput {
+    fn output(&self, traces: &[TransformTrace], key: &Path) -> AppResult<()> {
+        let mut in_memory_ref = self
+            .value
+            .write()
+            .map_err(|_| AppError::FileSystem("Cannot write trace output".to_string()))?;
+        in_memory_ref.insert(key.to_str().unwrap().to_string(), traces.to_vec());
+        Ok(())
+    }
+}
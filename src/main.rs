@@ -1,19 +1,89 @@
-use clap::Parser;
-use std::{collections::HashMap, error::Error, path::PathBuf, sync::Arc, thread::JoinHandle};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use std::{
+    collections::HashMap, error::Error, path::PathBuf, sync::Arc, thread::JoinHandle,
+    time::Duration,
+};
 use yml_assembler::{
-    adapters::{AssemblyOutputFormat, PartReaderPort},
+    adapters::{AssemblyOutputFormat, Dependencies, PartReaderPort, SchemaReaderPort},
+    graph::IncludeGraph,
     lib_infras::{
-        assembly_fs_output::AssemblyFSOutput, assembly_part_fs_reader::PartFSReader,
-        schema_fs_output::SchemaFSOutput, schema_fs_reader::SchemaFSReader,
+        archive::is_archive, assembly_part_archive_reader::PartArchiveReader,
+        assembly_part_fs_reader::PartFSReader, build_cache_fs::BuildCacheFS, fs_watcher::FSWatcher,
+        schema_archive_reader::SchemaArchiveReader, schema_fs_reader::SchemaFSReader,
+        trace_fs_output::TraceFSOutput,
     },
+    options::AssembleOptions,
+    utils::result::AppResult,
+    App,
 };
 
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(
+    author,
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Cli {
-    /// The directory your pyml files reside in
-    #[arg(short, long)]
-    root: PathBuf,
+    #[command(flatten)]
+    assemble: Option<AssembleArgs>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Assemble the entries, then assemble again the ones affected by each file change
+    Watch {
+        #[command(flatten)]
+        assemble: AssembleArgs,
+
+        /// Milliseconds between two looks for changed files
+        #[arg(long, default_value = "500")]
+        interval: u64,
+    },
+    /// Write the include graph of the entries in the output folder, without assembling them
+    Graph {
+        #[command(flatten)]
+        assemble: AssembleArgs,
+
+        /// The format of the graph file
+        #[arg(long, default_value = "dot", value_enum)]
+        to: GraphFormat,
+    },
+    /// List the entries including a part, directly or through other parts
+    Uses {
+        /// The part to look for, like tags/horror
+        part: String,
+
+        #[command(flatten)]
+        assemble: AssembleArgs,
+    },
+    /// Tell where the value at a key of the entry came from
+    Explain {
+        /// The dotted key to explain, like covers.2.color
+        key: String,
+
+        #[command(flatten)]
+        assemble: AssembleArgs,
+    },
+}
+
+#[derive(ValueEnum, Clone, Debug)]
+enum GraphFormat {
+    Dot,
+    Mermaid,
+    Json,
+}
+
+#[derive(Args, Debug)]
+struct AssembleArgs {
+    /// The directory (or .tar, .tar.gz, .zip archive) your pyml files reside in, repeat it to overlay several directories (first wins)
+    #[arg(short, long, required = true)]
+    root: Vec<PathBuf>,
 
     /// The path to the pyml file to assemble (relative to root)
     #[arg(short, long)]
@@ -31,9 +101,33 @@ struct Cli {
     #[clap(long, short = 'f', default_value = "yml", value_enum)]
     format: AssemblyOutputFormat,
 
+    /// Directories parts can include with a prefix, like common=../shared/parts for !inc::@common/tags/adult
+    #[arg(short, long, value_parser = parse_key_val::<String, PathBuf>)]
+    alias: Vec<(String, PathBuf)>,
+
+    /// Extensions of the part files, repeat it to accept several (first wins on conflicts)
+    #[arg(long = "extension", default_value = "pyml")]
+    extensions: Vec<String>,
+
+    /// Allow parts, resources and schemas to be read outside of the roots and aliases
+    #[arg(long)]
+    allow_outside_root: bool,
+
     /// Variables to insert in the pyml assembly
     #[arg(short, long, value_parser = parse_key_val::<String, String>)]
     vars: Option<Vec<(String, String)>>,
+
+    /// Keep a record of each assembled entry in this directory and skip entries whose inputs did not change
+    #[arg(long)]
+    cache_dir: Option<PathBuf>,
+
+    /// Write the assignments done by _transform formulas next to each output file
+    #[arg(long)]
+    trace_transforms: bool,
+
+    /// Fail on warnings, like a missing optional part
+    #[arg(long)]
+    strict: bool,
 }
 
 static DEFAULT_OUTPUT: &str = "output";
@@ -52,97 +146,417 @@ where
     Ok((s[..pos].parse()?, s[pos + 1..].parse()?))
 }
 
-fn cli() -> Result<(), anyhow::Error> {
-    let Cli {
-        output,
-        entry,
-        root,
-        schema,
-        vars,
-        format,
-    } = Cli::parse();
+type Readers = (Arc<dyn PartReaderPort>, Arc<dyn SchemaReaderPort>);
 
-    let display_variables = format!(
-        "Using variables:{}",
-        &vars
-            .clone()
-            .unwrap_or(vec![])
-            .iter()
-            .fold("".to_string(), |acc, (k, v)| format!(
-                "{}\n{}={}",
-                acc, k, v
-            ))
+/// Reads from a single archive root, or from the overlaid directory roots
+fn get_readers(
+    root: Vec<PathBuf>,
+    alias: Vec<(String, PathBuf)>,
+    extensions: Vec<String>,
+    allow_outside_root: bool,
+) -> Result<Readers, anyhow::Error> {
+    if let [archive] = root.as_slice() {
+        if is_archive(archive) {
+            if !alias.is_empty() {
+                anyhow::bail!("Aliases can't be used with an archive root");
+            }
+            let part_reader = PartArchiveReader::open(archive)?.with_extensions(extensions);
+            let schema_reader = SchemaArchiveReader::open(archive)?.with_log(true);
+            return match allow_outside_root {
+                true => Ok((
+                    Arc::new(part_reader.allow_outside_roots()),
+                    Arc::new(schema_reader.allow_outside_roots()),
+                )),
+                false => Ok((Arc::new(part_reader), Arc::new(schema_reader))),
+            };
+        }
+    }
+    if root.iter().any(|root| is_archive(root)) {
+        anyhow::bail!("An archive root can't be combined with other roots");
+    }
+
+    let part_fs_reader = alias.into_iter().fold(
+        PartFSReader::with_roots(root.clone())
+            .with_extensions(extensions)
+            .with_log(true),
+        |reader, (name, path)| {
+            println!(
+                "Using alias: @{} -> {}",
+                name.trim_start_matches('@'),
+                path.display()
+            );
+            reader.with_alias(&name, path)
+        },
     );
-    let variables: HashMap<String, String> =
-        HashMap::from_iter(vars.unwrap_or_default().into_iter());
+    let schema_fs_reader = SchemaFSReader::with_roots(root).with_log(true);
+
+    match allow_outside_root {
+        true => Ok((
+            Arc::new(part_fs_reader.allow_outside_roots()),
+            Arc::new(schema_fs_reader.allow_outside_roots()),
+        )),
+        false => Ok((Arc::new(part_fs_reader), Arc::new(schema_fs_reader))),
+    }
+}
+
+/// An app set up from the command line, with what it needs to assemble entries
+struct Session {
+    app: App,
+    part_reader: Arc<dyn PartReaderPort>,
+    entry: String,
+    schema: Option<String>,
+    variables: HashMap<String, String>,
+    format: AssemblyOutputFormat,
+    outdir: PathBuf,
+    cache_dir: Option<PathBuf>,
+    extensions: Vec<String>,
+    /// Directories parts are read from, with the prefix their identifiers get
+    dirs: Vec<(PathBuf, String)>,
+}
+
+impl Session {
+    fn open(args: AssembleArgs) -> Result<Self, anyhow::Error> {
+        let AssembleArgs {
+            output,
+            entry,
+            root,
+            alias,
+            allow_outside_root,
+            extensions,
+            schema,
+            vars,
+            format,
+            trace_transforms,
+            cache_dir,
+            strict,
+        } = args;
+
+        let display_variables = format!(
+            "Using variables:{}",
+            &vars
+                .clone()
+                .unwrap_or(vec![])
+                .iter()
+                .fold("".to_string(), |acc, (k, v)| format!(
+                    "{}\n{}={}",
+                    acc, k, v
+                ))
+        );
+        let variables: HashMap<String, String> = HashMap::from_iter(vars.unwrap_or_default());
+
+        let outdir = PathBuf::from(DEFAULT_OUTPUT);
+        let outdir = output.unwrap_or(outdir);
+
+        println!("{}", display_variables);
+        println!("Using format: {:?}", format);
+        println!(
+            "Working in: {}",
+            root.iter()
+                .map(|root| root.display().to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        );
+        if let Some(schema) = schema.as_deref() {
+            println!("Validating from schema: {}", schema);
+        }
+        println!("Outputing in: {}", outdir.display());
+
+        let dirs =
+            root.iter()
+                .map(|root| (root.clone(), String::new()))
+                .chain(alias.iter().map(|(name, path)| {
+                    (path.clone(), format!("@{}/", name.trim_start_matches('@')))
+                }))
+                .collect();
+
+        let builder = App::builder(root[0].clone());
+        let (part_reader, schema_reader) =
+            get_readers(root, alias, extensions.clone(), allow_outside_root)?;
+
+        let builder = builder
+            .with_part_reader(Arc::clone(&part_reader))
+            .with_schema_reader(schema_reader)
+            .with_output_dir(outdir.clone())
+            .with_options(
+                AssembleOptions::default()
+                    .with_format(format.clone())
+                    .with_strict(strict)
+                    .with_log(true),
+            );
+        let builder = match trace_transforms {
+            true => builder.with_trace_output(Arc::new(TraceFSOutput::new(outdir.clone()))),
+            false => builder,
+        };
+        let app = match &cache_dir {
+            Some(cache_dir) => {
+                println!("Caching in: {}", cache_dir.display());
+                builder.with_build_cache(Arc::new(
+                    BuildCacheFS::new(cache_dir.clone()).with_log(true),
+                ))
+            }
+            None => builder,
+        }
+        .build();
+
+        Ok(Session {
+            app,
+            part_reader,
+            entry,
+            schema,
+            variables,
+            format,
+            outdir,
+            cache_dir,
+            extensions,
+            dirs,
+        })
+    }
+
+    fn entries(&self) -> AppResult<Vec<String>> {
+        self.part_reader.get_filepathes_from_glob(&self.entry)
+    }
 
-    let outdir = PathBuf::from(DEFAULT_OUTPUT);
-    let outdir = output.unwrap_or(outdir);
+    /// Assembles each entry in its own thread
+    fn build(
+        &self,
+        entries: &[String],
+    ) -> Result<Vec<(String, AppResult<Dependencies>)>, anyhow::Error> {
+        let wait_for_assemble = entries
+            .iter()
+            .map(|entry| {
+                let entry = entry.clone();
+                let app = self.app.clone();
+                let schema = self.schema.clone();
+                let variables = self.variables.clone();
+                let format = self.format.clone();
+
+                std::thread::spawn(move || {
+                    let result = app.build(&entry, schema.as_deref(), Some(variables), &format);
+                    (entry, result)
+                })
+            })
+            .collect::<Vec<JoinHandle<_>>>();
 
-    println!("{}", display_variables);
-    println!("Using format: {:?}", format);
-    println!("Working in: {}", root.display());
-    if let Some(schema) = schema.as_deref() {
-        println!("Validating from schema: {}", schema);
+        wait_for_assemble
+            .into_iter()
+            .map(|handle| handle.join())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| anyhow::anyhow!(format!("Could not join thread: {:?}", e)))
     }
-    println!("Outputing in: {}", outdir.display());
 
-    let part_fs_reader = PartFSReader::new(root.clone());
-    let entries = part_fs_reader.get_filepathes_from_glob(&entry)?;
+    /// Identifiers a changed file may be known by: its path, and its part identifier
+    fn identifiers(&self, file: &str) -> Vec<String> {
+        let part = file
+            .rsplit_once('.')
+            .filter(|(_, extension)| self.extensions.iter().any(|e| e == extension))
+            .map(|(identifier, _)| identifier.to_string());
+
+        std::iter::once(file.to_string()).chain(part).collect()
+    }
+}
+
+fn assemble(args: AssembleArgs) -> Result<(), anyhow::Error> {
+    let session = Session::open(args)?;
+    let entries = session.entries()?;
     println!("Assembling files: {}", entries.clone().join(" "));
 
-    let schema_fs_reader = SchemaFSReader::new(root.clone());
-    let assembly_fs_output = AssemblyFSOutput::new(outdir.clone());
-    let schema_fs_output = SchemaFSOutput::new(outdir.clone());
+    for (_, result) in session.build(&entries)? {
+        if let Err(e) = result {
+            anyhow::bail!(format!("Could not assemble: {:?}", e));
+        }
+    }
+    println!("Assembling done!");
+    Ok(())
+}
 
-    let app = yml_assembler::App::new(
-        Arc::new(part_fs_reader),
-        Arc::new(schema_fs_reader),
-        Arc::new(assembly_fs_output),
-        Arc::new(schema_fs_output),
-    );
+fn watch(args: AssembleArgs, interval: u64) -> Result<(), anyhow::Error> {
+    let session = Session::open(args)?;
+    if session.dirs.iter().any(|(dir, _)| is_archive(dir)) {
+        anyhow::bail!("An archive root can't be watched");
+    }
 
-    let wait_for_assemble = entries
-        .iter()
-        .map(|entry| {
-            let entry = entry.clone();
-            let app = app.clone();
-            let schema = schema.clone();
-            let variables = variables.clone();
-            let format = format.clone();
-
-            std::thread::spawn(move || {
-                app.clone().compile_and_validate_yml(
-                    &entry,
-                    schema.as_deref(),
-                    Some(variables.clone()),
-                    &format,
-                )
+    let report = |results: Vec<(String, AppResult<Dependencies>)>,
+                  dependencies: &mut HashMap<String, Dependencies>| {
+        for (entry, result) in results {
+            match result {
+                Ok(entry_dependencies) => {
+                    println!("assembled: {}", entry);
+                    dependencies.insert(entry, entry_dependencies);
+                }
+                Err(e) => {
+                    println!("failed: {}: {}", entry, e);
+                    dependencies.remove(&entry);
+                }
+            }
+        }
+    };
+
+    let mut dependencies: HashMap<String, Dependencies> = HashMap::new();
+    // Outputs, traces and cached builds are written there, they would trigger rebuilds.
+    let excluded = std::iter::once(session.outdir.clone())
+        .chain(session.cache_dir.clone())
+        .collect();
+    let mut watcher = FSWatcher::new(session.dirs.clone(), excluded)?;
+    let entries = session.entries()?;
+    println!("Assembling files: {}", entries.clone().join(" "));
+    report(session.build(&entries)?, &mut dependencies);
+    println!("Watching for changes...");
+
+    loop {
+        std::thread::sleep(Duration::from_millis(interval));
+        // A file vanishing while it is scanned or built fails one round, not the watch.
+        let changes = match watcher.changes() {
+            Ok(changes) => changes,
+            Err(e) => {
+                println!("failed: {}", e);
+                continue;
+            }
+        };
+        if changes.is_empty() {
+            continue;
+        }
+        println!("changed: {}", changes.join(" "));
+
+        let schema_changed = session
+            .schema
+            .as_ref()
+            .is_some_and(|schema| changes.contains(schema));
+        let changes = changes
+            .iter()
+            .flat_map(|file| session.identifiers(file))
+            .collect::<Vec<String>>();
+
+        let entries = match session.entries() {
+            Ok(entries) => entries,
+            Err(e) => {
+                println!("failed: {}: {}", session.entry, e);
+                continue;
+            }
+        };
+        dependencies.retain(|entry, _| entries.contains(entry));
+
+        let affected = entries
+            .into_iter()
+            .filter(|entry| {
+                schema_changed
+                    || match dependencies.get(entry) {
+                        Some(entry_dependencies) => changes
+                            .iter()
+                            .any(|change| entry_dependencies.is_affected_by(change)),
+                        None => true,
+                    }
             })
-        })
-        .collect::<Vec<JoinHandle<_>>>();
+            .collect::<Vec<String>>();
 
-    let join_handle_result: Result<(), anyhow::Error> = wait_for_assemble
-        .into_iter()
-        .map(|handle| handle.join())
-        .collect::<Result<Vec<_>, _>>()
-        .map_err(|e| anyhow::anyhow!(format!("Could not join thread: {:?}", e)))?
+        match session.build(&affected) {
+            Ok(results) => report(results, &mut dependencies),
+            Err(e) => println!("failed: {}", e),
+        }
+    }
+}
+
+fn graph(args: AssembleArgs, to: GraphFormat) -> Result<(), anyhow::Error> {
+    let session = Session::open(args)?;
+    let entries = session.entries()?;
+    println!("Graphing files: {}", entries.clone().join(" "));
+
+    let graph = session
+        .app
+        .graph(&entries, Some(session.variables.clone()))?;
+    report_graph_failures(&graph);
+    let (graph, extension) = match to {
+        GraphFormat::Dot => (graph.to_dot(), "dot"),
+        GraphFormat::Mermaid => (graph.to_mermaid(), "mmd"),
+        GraphFormat::Json => (graph.to_json()?, "json"),
+    };
+
+    std::fs::create_dir_all(&session.outdir)?;
+    let graph_path = session.outdir.join(format!("graph.{extension}"));
+    std::fs::write(&graph_path, graph)?;
+    println!("Graph written in: {}", graph_path.display());
+
+    Ok(())
+}
+
+fn uses(args: AssembleArgs, part: String) -> Result<(), anyhow::Error> {
+    let session = Session::open(args)?;
+    let entries = session.entries()?;
+
+    let graph = session
+        .app
+        .graph(&entries, Some(session.variables.clone()))?;
+    report_graph_failures(&graph);
+    let users = graph.entries_using(&part);
+
+    println!("Entries using {}:", part);
+    users.iter().for_each(|entry| println!("{}", entry));
+
+    Ok(())
+}
+
+fn report_graph_failures(graph: &IncludeGraph) {
+    graph
+        .failures
         .iter()
-        .try_fold((), |acc, x| {
-            if let Err(e) = x {
-                Err(anyhow::anyhow!(format!("Could not assemble: {:?}", e)))?;
-            }
-            Ok(acc)
-        });
+        .for_each(|(entry, e)| println!("warning: could not load {}: {}", entry, e));
+}
 
-    join_handle_result?;
+fn explain(args: AssembleArgs, key: String) -> Result<(), anyhow::Error> {
+    let session = Session::open(args)?;
+    let explanation = session
+        .app
+        .explain(&session.entry, &key, Some(session.variables.clone()))?;
+
+    match &explanation.value {
+        Some(value) => println!("{} = {}", key, serde_json::to_string(value)?),
+        None => println!("{} is not set in {}", key, session.entry),
+    }
+    explanation
+        .steps
+        .iter()
+        .enumerate()
+        .for_each(|(index, step)| println!("{}. {}", index + 1, step));
 
     Ok(())
 }
 
+fn cli() -> Result<(), anyhow::Error> {
+    let Cli {
+        assemble: args,
+        command,
+    } = Cli::parse();
+
+    match (command, args) {
+        (
+            Some(Command::Watch {
+                assemble: args,
+                interval,
+            }),
+            _,
+        ) => watch(args, interval),
+        (Some(Command::Graph { assemble: args, to }), _) => graph(args, to),
+        (
+            Some(Command::Uses {
+                assemble: args,
+                part,
+            }),
+            _,
+        ) => uses(args, part),
+        (
+            Some(Command::Explain {
+                assemble: args,
+                key,
+            }),
+            _,
+        ) => explain(args, key),
+        (None, Some(args)) => assemble(args),
+        (None, None) => anyhow::bail!("Missing --root and --entry"),
+    }
+}
+
 fn main() {
     match cli() {
-        Ok(_) => println!("Assembling done!"),
+        Ok(_) => {}
         Err(e) => {
             println!("Error: {}", e);
             std::process::exit(1);
@@ -1,9 +1,16 @@
 use super::MixIns;
-use crate::utils::result::{AppError, AppResult};
+use crate::{
+    options::MergePolicy,
+    utils::result::{AppError, AppResult},
+};
 use serde_yaml::{Mapping, Value};
 
 impl MixIns {
     pub fn inject(&self, injected: &Value) -> AppResult<Value> {
+        self.inject_with(injected, MergePolicy::Append)
+    }
+
+    pub fn inject_with(&self, injected: &Value, merge: MergePolicy) -> AppResult<Value> {
         fn merge_values(val_base: &Value, val_mix: &Value) -> AppResult<Value> {
             let val_base = val_base.clone();
             let val_mix = val_mix.clone();
@@ -42,10 +49,10 @@ impl MixIns {
         }
 
         fn get_entry_to_mix_on<'a>(key: &str, val: &'a mut Value) -> AppResult<&'a mut Value> {
-            let mut parts = key.split(".").into_iter();
+            let parts = key.split(".");
 
             let mut val_to_be_mix_on = val;
-            while let Some(part) = parts.next() {
+            for part in parts {
                 let entry = match val_to_be_mix_on.clone() {
                     Value::Null => match part.parse::<usize>() {
                         Ok(index) => {
@@ -58,16 +65,16 @@ impl MixIns {
                             let mut map = Mapping::new();
                             map.insert(Value::String(part.to_string()), Value::Null);
                             *val_to_be_mix_on = Value::Mapping(map);
-                            val_to_be_mix_on.get_mut(&Value::String(part.to_string()))
+                            val_to_be_mix_on.get_mut(Value::String(part.to_string()))
                         }
                     },
                     Value::Mapping(map) => {
-                        let entry = map.get(&Value::String(part.to_string()));
+                        let entry = map.get(Value::String(part.to_string()));
                         let map = val_to_be_mix_on.as_mapping_mut().unwrap();
                         if entry.is_none() {
                             map.insert(Value::String(part.to_string()), Value::Null);
                         }
-                        map.get_mut(&Value::String(part.to_string()))
+                        map.get_mut(Value::String(part.to_string()))
                     }
                     Value::Sequence(_) => match part.parse::<usize>() {
                         Ok(index) => {
@@ -108,8 +115,11 @@ impl MixIns {
 
                         let final_value: Value = values_to_inject.iter().try_fold(
                             entry_to_inject.clone(),
-                            |entry_to_inject, value_to_inject| {
-                                merge_values(&entry_to_inject, &value_to_inject)
+                            |entry_to_inject, value_to_inject| match merge {
+                                MergePolicy::Append => {
+                                    merge_values(&entry_to_inject, value_to_inject)
+                                }
+                                MergePolicy::Replace => Ok(value_to_inject.clone()),
                             },
                         )?;
 
@@ -130,6 +140,18 @@ impl MixIns {
 mod test {
     use super::*;
 
+    #[test]
+    fn it_should_keep_last_mixin_when_replacing() {
+        let root_yml: Value = serde_yaml::from_str("toto: some_toto").unwrap();
+        let mut mixin = MixIns::new();
+        mixin.add("toto".to_string(), vec!["a".into(), "b".into()]);
+
+        let injected_yml = mixin.inject_with(&root_yml, MergePolicy::Replace).unwrap();
+
+        let expected_yml: Value = serde_yaml::from_str("toto: b").unwrap();
+        assert_eq!(injected_yml, expected_yml);
+    }
+
     #[test]
     fn it_should_mix_as_sequence_when_origin_is_leaf() {
         let root_yml: Value = serde_yaml::from_str(
@@ -1,25 +1,12 @@
 use serde_yaml::Value;
-use std::{
-    collections::HashMap,
-    ops::{Deref, DerefMut},
-};
+use std::collections::HashMap;
 
 mod inject;
 mod trim;
 
-#[derive(Debug)]
+/// Values declared with `!mix`, by the dotted key they are mixed into.
+#[derive(Debug, Default)]
 pub struct MixIns(HashMap<String, Vec<Value>>);
-impl Deref for MixIns {
-    type Target = HashMap<String, Vec<Value>>;
-    fn deref(&self) -> &Self::Target {
-        &self.0
-    }
-}
-impl DerefMut for MixIns {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
-    }
-}
 
 impl MixIns {
     pub fn new() -> Self {
@@ -33,7 +20,28 @@ impl MixIns {
     }
 
     pub fn add(&mut self, key: String, value: Vec<Value>) {
-        let entry = self.entry(key).or_insert_with(Vec::new);
+        let entry = self.0.entry(key).or_default();
         entry.append(&mut value.clone());
     }
+
+    /// Values mixed into `key`, in declaration order.
+    pub fn get(&self, key: &str) -> Option<&Vec<Value>> {
+        self.0.get(key)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.0.keys()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Vec<Value>)> {
+        self.0.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
 }
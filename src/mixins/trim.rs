@@ -1,5 +1,8 @@
 use super::MixIns;
-use crate::utils::result::{AppError, AppResult};
+use crate::{
+    aggregator::YmlAggregator,
+    utils::result::{AppError, AppResult},
+};
 use serde_yaml::{
     value::{Tag, TaggedValue},
     Mapping, Value,
@@ -21,6 +24,11 @@ impl MixIns {
         let tag = &val.tag.to_string();
         let value = &val.value;
 
+        // Loop bodies are trimmed by the aggregator, once per iteration.
+        if tag == YmlAggregator::EACH_TAG {
+            return Ok(Value::Tagged(Box::new(val.clone())));
+        }
+
         let yml = self.trim(value)?;
         Ok(Value::Tagged(Box::new(TaggedValue {
             tag: Tag::new(tag),
@@ -31,7 +39,7 @@ impl MixIns {
     fn on_sequence(&mut self, val: &Vec<Value>) -> AppResult<Value> {
         let mut new_seq: Vec<Value> = vec![];
         for value in val {
-            let yml = self.trim(&value)?;
+            let yml = self.trim(value)?;
             new_seq.push(yml)
         }
         Ok(Value::Sequence(new_seq))
@@ -58,16 +66,13 @@ impl MixIns {
                                 }
                             };
 
-                            self.entry(key.clone()).or_insert(Vec::new()).push(yml);
+                            self.0.entry(key.clone()).or_default().push(yml);
                             Ok(None)
                         }
-                        false => Ok(Some(Value::Tagged(Box::new(TaggedValue {
-                            tag: Tag::new(tag),
-                            value: self.trim(value)?,
-                        })))),
+                        false => self.on_tag(t).map(Some),
                     }
                 }
-                _ => self.trim(value).map(|x| Some(x)),
+                _ => self.trim(value).map(Some),
             }?;
 
             if let Some(value) = value {
@@ -0,0 +1,68 @@
+use crate::adapters::AssemblyOutputFormat;
+
+/// How entries are assembled, new options get a default so callers keep compiling.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct AssembleOptions {
+    /// Fails on warnings, like a missing optional part, instead of reporting them.
+    pub strict: bool,
+    pub merge: MergePolicy,
+    pub evaluation: EvaluationMode,
+    pub format: AssemblyOutputFormat,
+    /// Prints warnings and entries found up to date.
+    pub log: bool,
+}
+impl Default for AssembleOptions {
+    fn default() -> Self {
+        AssembleOptions {
+            strict: false,
+            merge: MergePolicy::Append,
+            evaluation: EvaluationMode::Evaluate,
+            format: AssemblyOutputFormat::Yml,
+            log: false,
+        }
+    }
+}
+impl AssembleOptions {
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn with_merge(mut self, merge: MergePolicy) -> Self {
+        self.merge = merge;
+        self
+    }
+
+    pub fn with_evaluation(mut self, evaluation: EvaluationMode) -> Self {
+        self.evaluation = evaluation;
+        self
+    }
+
+    pub fn with_format(mut self, format: AssemblyOutputFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_log(mut self, log: bool) -> Self {
+        self.log = log;
+        self
+    }
+}
+
+/// How `!mix` values are merged into the value of their key.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MergePolicy {
+    /// Mappings are extended, sequences appended to and leaves gathered in a sequence.
+    Append,
+    /// Each mixed value replaces the previous one, the last one wins.
+    Replace,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EvaluationMode {
+    /// Applies `_transform` formulas.
+    Evaluate,
+    /// Keeps `_transform` formulas in the assembled value without applying them.
+    Skip,
+}
@@ -1,145 +1,148 @@
 use super::TransformableList;
 use crate::utils::result::{AppError, AppResult};
 use serde_yaml::{Mapping, Sequence, Value};
+use std::collections::BTreeMap;
 
 static OPERATIONS_KEY: &str = "_transform";
 
-fn value_convert(val: &evalexpr::Value) -> AppResult<serde_yaml::Value> {
+pub(super) fn value_convert(val: &evalexpr::Value) -> AppResult<serde_yaml::Value> {
     match val {
-        evalexpr::Value::String(s) => return Ok(Value::String(s.clone())),
+        evalexpr::Value::String(s) => Ok(Value::String(s.clone())),
         evalexpr::Value::Float(n) => {
             let n_int: Option<i64> = {
                 let is_int = n.fract() == 0.0;
                 let is_int = is_int && n < &(i64::MAX as f64);
                 let is_int = is_int && n > &(i64::MIN as f64);
                 match is_int {
-                    true => Some(n.clone() as i64),
+                    true => Some(*n as i64),
                     false => None,
                 }
             };
 
             match n_int {
-                Some(i_int) => return Ok(Value::Number(serde_yaml::Number::from(i_int))),
-                None => return Ok(Value::Number(serde_yaml::Number::from(n.clone()))),
+                Some(i_int) => Ok(Value::Number(serde_yaml::Number::from(i_int))),
+                None => Ok(Value::Number(serde_yaml::Number::from(*n))),
             }
         }
-        evalexpr::Value::Int(n) => return Ok(Value::Number(serde_yaml::Number::from(n.clone()))),
-        evalexpr::Value::Boolean(b) => return Ok(Value::Bool(b.clone())),
-        evalexpr::Value::Empty => return Ok(Value::Null),
-        _ => Err(AppError::ApplyFormula(format!(
-            "Can't convert {val:?} to yml",
-        )))?,
+        evalexpr::Value::Int(n) => Ok(Value::Number(serde_yaml::Number::from(*n))),
+        evalexpr::Value::Boolean(b) => Ok(Value::Bool(*b)),
+        evalexpr::Value::Empty => Ok(Value::Null),
+        evalexpr::Value::Tuple(values) => Ok(Value::Sequence(
+            values
+                .iter()
+                .map(value_convert)
+                .collect::<AppResult<Sequence>>()?,
+        )),
     }
 }
 
-impl TryInto<Value> for TransformableList {
-    type Error = AppError;
+/// Intermediate tree used to rebuild the yml from the flat keys.
+/// Sequence items are ordered by index and the missing indexes are dropped,
+/// so that `list.5 = x` appends to a shorter list and deleted items leave no hole.
+/// A sequence receiving a non numeric key becomes a mapping keyed by the former indexes.
+enum Node {
+    Leaf(Value),
+    Mapping(Vec<(String, Node)>),
+    Sequence(BTreeMap<usize, Node>),
+}
 
-    fn try_into(self) -> Result<Value, Self::Error> {
-        if self.is_empty() {
-            return Ok(Value::Null);
+impl Node {
+    fn new_container(part: &str) -> Self {
+        match part.parse::<usize>() {
+            Ok(_) => Node::Sequence(BTreeMap::new()),
+            Err(_) => Node::Mapping(vec![]),
         }
+    }
 
-        let (first_key, first_value) = self.iter().next().unwrap();
+    fn insert(&mut self, key: &str, parts: &[&str], value: Value) -> AppResult<()> {
+        let (part, rest) = match parts.split_first() {
+            Some(split) => split,
+            None => {
+                *self = Node::Leaf(value);
+                return Ok(());
+            }
+        };
 
-        if first_key == "" {
-            return value_convert(first_value);
+        if let Node::Sequence(seq) = self {
+            if part.parse::<usize>().is_err() {
+                let entries = std::mem::take(seq)
+                    .into_iter()
+                    .map(|(index, node)| (index.to_string(), node))
+                    .collect();
+                *self = Node::Mapping(entries);
+            }
         }
 
-        let first_part = first_key
-            .split('.')
-            .next()
-            .ok_or_else(|| AppError::ApplyFormula(format!("No key segment found")))?;
-
-        let first_part_as_number = first_part.parse::<usize>();
-        let first_part_as_string = first_part.parse::<String>();
-
-        let mut yml = match (first_part_as_number, first_part_as_string) {
-            (Ok(_), _) => Value::Sequence(Sequence::new()),
-            (_, Ok(_)) => Value::Mapping(Mapping::new()),
-            _ => Err(AppError::ApplyFormula(format!(
-                "{first_part:?} is not a valid key",
+        let child = match self {
+            Node::Sequence(seq) => {
+                let index = part.parse::<usize>().map_err(|_| {
+                    AppError::ApplyFormula(format!("Expected a number, got {part:?}"))
+                })?;
+                seq.entry(index)
+                    .or_insert_with(|| Node::new_container(rest.first().unwrap_or(&"")))
+            }
+            Node::Mapping(map) => {
+                let index = match map.iter().position(|(k, _)| k == part) {
+                    Some(index) => index,
+                    None => {
+                        let child = Node::new_container(rest.first().unwrap_or(&""));
+                        map.push((part.to_string(), child));
+                        map.len() - 1
+                    }
+                };
+                &mut map[index].1
+            }
+            Node::Leaf(_) => Err(AppError::ApplyFormula(format!(
+                "Can't insert {key:?}, {part:?} is nested in a leaf"
             )))?,
         };
 
-        for (key, value) in &*self {
-            let mut current = &mut yml;
-            let mut parts = key.split('.').peekable();
-            let value = value_convert(value)?;
+        child.insert(key, rest, value)
+    }
+}
 
-            while let Some(part) = parts.next() {
-                enum NextPart {
-                    Number(usize),
-                    String(String),
-                    None,
-                }
-                impl NextPart {
-                    fn try_new(next_part: Option<&str>) -> AppResult<Self> {
-                        match next_part {
-                            Some(part) => match (part.parse::<usize>(), part.parse::<String>()) {
-                                (Ok(i), _) => Ok(NextPart::Number(i)),
-                                (_, Ok(s)) => Ok(NextPart::String(s)),
-                                _ => Err(AppError::ApplyFormula(format!(
-                                    "{next_part:?} is not a valid key",
-                                )))?,
-                            },
-                            None => Ok(NextPart::None),
-                        }
-                    }
+impl From<Node> for Value {
+    fn from(node: Node) -> Self {
+        match node {
+            Node::Leaf(value) => value,
+            Node::Mapping(map) => Value::Mapping(
+                map.into_iter()
+                    .map(|(key, node)| (Value::String(key), node.into()))
+                    .collect::<Mapping>(),
+            ),
+            Node::Sequence(seq) => Value::Sequence(
+                seq.into_values()
+                    .map(|node| node.into())
+                    .collect::<Sequence>(),
+            ),
+        }
+    }
+}
 
-                    fn to_next_container_or_value(&self, val: &Value) -> Value {
-                        match self {
-                            NextPart::Number(_) => Value::Sequence(Sequence::new()),
-                            NextPart::String(_) => Value::Mapping(Mapping::new()),
-                            NextPart::None => val.clone(),
-                        }
-                    }
-                }
+impl TryInto<Value> for TransformableList {
+    type Error = AppError;
 
-                let next_part = NextPart::try_new(parts.peek().map(|x| *x))?;
+    fn try_into(self) -> Result<Value, Self::Error> {
+        let (first_key, _) = match self.list.first() {
+            Some(first) => first,
+            None => return Ok(Value::Null),
+        };
 
-                match current {
-                    Value::Sequence(seq) => {
-                        let index = part.parse::<usize>().map_err(|_| {
-                            AppError::ApplyFormula(format!("Expected a number, got {part:?}"))
-                        })?;
-                        if index >= seq.len() {
-                            seq.resize_with(index + 1, || Value::Null);
-                        }
-                        let entry = seq.get_mut(index).ok_or_else(|| {
-                            AppError::ApplyFormula(format!("Nothing at {part:?}"))
-                        })?;
-                        match entry {
-                            Value::Null => {
-                                *entry = next_part.to_next_container_or_value(&value);
-                            }
-                            _ => {}
-                        }
-                        current = seq.get_mut(index).ok_or_else(|| {
-                            AppError::ApplyFormula(format!("Can't get mutable at {part:?}"))
-                        })?;
-                    }
-                    Value::Mapping(map) => {
-                        let key = part.parse::<String>().map_err(|_| {
-                            AppError::ApplyFormula(format!("Expected a string, got {part:?}"))
-                        })?;
-                        let entry = map.get(&key);
-                        if let None = entry {
-                            map.insert(
-                                Value::String(key.clone()),
-                                next_part.to_next_container_or_value(&value),
-                            );
-                        }
-                        current = map.get_mut(key).unwrap();
-                    }
-                    _ => Err(AppError::ApplyFormula(format!(
-                        "Can only insert something in mapping or sequence, got {current:?}"
-                    )))?,
-                }
-            }
+        let first_part = first_key.split('.').next().unwrap_or_default();
+        let mut root = match first_key.is_empty() {
+            true => Node::Leaf(Value::Null),
+            false => Node::new_container(first_part),
+        };
+
+        for (key, value) in &self.list {
+            let parts = match key.is_empty() {
+                true => vec![],
+                false => key.split('.').collect::<Vec<&str>>(),
+            };
+            root.insert(key, &parts, value_convert(value)?)?;
         }
 
-        Ok(yml)
+        Ok(root.into())
     }
 }
 
@@ -147,27 +150,29 @@ impl TryFrom<Value> for TransformableList {
     type Error = AppError;
 
     fn try_from(value: Value) -> AppResult<Self> {
-        fn visit(val: &Value, parent_key: &str) -> AppResult<TransformableList> {
-            let mut transformable_list = TransformableList::new(None);
+        /// Flattens `val` into `list`, keys being unique there is no entry to replace.
+        fn visit(
+            val: &Value,
+            parent_key: &str,
+            list: &mut Vec<(String, evalexpr::Value)>,
+        ) -> AppResult<()> {
             match val {
                 Value::String(s) => {
-                    transformable_list
-                        .set(format!("{parent_key}"), evalexpr::Value::String(s.clone()));
+                    list.push((parent_key.to_string(), evalexpr::Value::String(s.clone())));
                 }
                 Value::Bool(s) => {
-                    transformable_list
-                        .set(format!("{parent_key}"), evalexpr::Value::Boolean(s.clone()));
+                    list.push((parent_key.to_string(), evalexpr::Value::Boolean(*s)));
                 }
                 Value::Number(s) => {
-                    transformable_list.set(
-                        format!("{parent_key}"),
+                    list.push((
+                        parent_key.to_string(),
                         evalexpr::Value::Float(s.as_f64().ok_or_else(|| {
-                            AppError::ApplyFormula(format!("Your numbers must be f64"))
+                            AppError::ApplyFormula("Your numbers must be f64".to_string())
                         })?),
-                    );
+                    ));
                 }
                 Value::Null => {
-                    transformable_list.set(format!("{parent_key}"), evalexpr::Value::Empty);
+                    list.push((parent_key.to_string(), evalexpr::Value::Empty));
                 }
                 Value::Mapping(m) => {
                     for (key, v) in m {
@@ -182,12 +187,10 @@ impl TryFrom<Value> for TransformableList {
                         }?;
 
                         let new_key = match parent_key {
-                            "" => format!("{k}"),
+                            "" => k.to_string(),
                             _ => format!("{parent_key}.{k}"),
                         };
-                        let child_flat_yml = visit(v, &new_key)?;
-                        transformable_list
-                            .extend(child_flat_yml.iter().map(|(k, v)| (k.clone(), v.clone())));
+                        visit(v, &new_key, list)?;
                     }
                 }
                 Value::Sequence(seq) => {
@@ -196,9 +199,7 @@ impl TryFrom<Value> for TransformableList {
                             "" => format!("{i}"),
                             _ => format!("{parent_key}.{i}"),
                         };
-                        let child_flat_yml = visit(v, &new_key)?;
-                        transformable_list
-                            .extend(child_flat_yml.iter().map(|(k, v)| (k.clone(), v.clone())));
+                        visit(v, &new_key, list)?;
                     }
                 }
                 Value::Tagged(t) => {
@@ -207,7 +208,7 @@ impl TryFrom<Value> for TransformableList {
                 }
             }
 
-            Ok(transformable_list)
+            Ok(())
         }
 
         let mut value = value.clone();
@@ -228,16 +229,17 @@ impl TryFrom<Value> for TransformableList {
                                     seq.clone(),
                                 ))
                                 .map_err(|_| {
-                                    AppError::ApplyFormula(format!(
-                                        "_transform should be a list of string"
-                                    ))
+                                    AppError::ApplyFormula(
+                                        "_transform should be a list of string".to_string(),
+                                    )
                                 })?;
                                 acc.extend(seq);
                                 Ok(acc)
                             }
-                            _ => Err(AppError::ApplyFormula(format!(
+                            _ => Err(AppError::ApplyFormula(
                                 "_transform should be composed of strings or of lists of string"
-                            ))),
+                                    .to_string(),
+                            )),
                         })?;
 
                         Some(transformations)
@@ -248,9 +250,9 @@ impl TryFrom<Value> for TransformableList {
                             .cloned()
                             .map(|k| match k {
                                 Value::String(s) => Ok(s.clone()),
-                                _ => Err(AppError::ApplyFormula(format!(
-                                    "_transform should be a mapping of string"
-                                ))),
+                                _ => Err(AppError::ApplyFormula(
+                                    "_transform should be a mapping of string".to_string(),
+                                )),
                             })
                             .collect::<AppResult<Vec<_>>>()?;
                         keys.sort();
@@ -263,15 +265,16 @@ impl TryFrom<Value> for TransformableList {
                                         let seq: Vec<String> =
                                             serde_yaml::from_value(Value::Sequence(seq.clone()))
                                                 .map_err(|_| {
-                                                    AppError::ApplyFormula(format!(
+                                                    AppError::ApplyFormula(
                                                         "_transform should be a list of string"
-                                                    ))
+                                                            .to_string(),
+                                                    )
                                                 })?;
                                         Ok(seq)
                                     }
-                                    _ => Err(AppError::ApplyFormula(format!(
-                                        "_transform should be a mapping of string"
-                                    ))),
+                                    _ => Err(AppError::ApplyFormula(
+                                        "_transform should be a mapping of string".to_string(),
+                                    )),
                                 }?;
 
                                 acc.extend(v);
@@ -291,12 +294,10 @@ impl TryFrom<Value> for TransformableList {
             _ => None,
         };
 
-        let transformable_list = visit(&value, "")?;
+        let mut transformable_list = TransformableList::new(operations);
+        visit(&value, "", &mut transformable_list.list)?;
 
-        Ok(Self {
-            operations,
-            ..transformable_list
-        })
+        Ok(transformable_list)
     }
 }
 
@@ -347,7 +348,7 @@ mod test {
         let test_yml = serde_yaml::to_value(&test_struct).unwrap();
         let trans_list = TransformableList::try_from(test_yml).unwrap();
 
-        assert_eq!(trans_list.len(), 8);
+        assert_eq!(trans_list.list.len(), 8);
         assert_eq!(
             trans_list.get("structure.sub_entry").unwrap(),
             &evalexpr::Value::String("I'm a sub entry".to_string())
@@ -481,16 +482,108 @@ mod test {
             _ => panic!("Should be a mapping"),
         };
 
-        match map.get(&Value::String("entry_int".to_string())) {
+        match map.get(Value::String("entry_int".to_string())) {
             Some(Value::Number(n)) => assert_eq!(n.as_f64().unwrap(), 2.2),
 
             _ => panic!("Should be a number"),
         };
 
-        match map.get(&Value::String("entry_float".to_string())) {
+        match map.get(Value::String("entry_float".to_string())) {
             Some(Value::Number(n)) => assert_eq!(n.as_u64().unwrap(), 2),
 
             _ => panic!("Should be a number"),
         };
     }
+
+    #[test]
+    fn it_should_output_tuples_as_sequences() {
+        let mut trans_list = TransformableList::new(None);
+        trans_list.set(
+            "tags".to_string(),
+            evalexpr::Value::Tuple(vec![
+                evalexpr::Value::String("adult".to_string()),
+                evalexpr::Value::Int(2),
+            ]),
+        );
+
+        let yml: Value = trans_list.try_into().unwrap();
+        let expected_yml: Value = serde_yaml::from_str(
+            r#"
+            tags:
+                - adult
+                - 2
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(yml, expected_yml);
+    }
+
+    #[test]
+    fn it_should_compact_sequences_in_index_order() {
+        let mut trans_list = TransformableList::new(None);
+        trans_list.set("list.5".to_string(), evalexpr::Value::Int(5));
+        trans_list.set("list.0".to_string(), evalexpr::Value::Int(0));
+        trans_list.set("list.2".to_string(), evalexpr::Value::Int(2));
+
+        let yml: Value = trans_list.try_into().unwrap();
+        let expected_yml: Value = serde_yaml::from_str(
+            r#"
+            list:
+                - 0
+                - 2
+                - 5
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(yml, expected_yml);
+    }
+
+    #[test]
+    fn it_should_turn_sequence_into_mapping_on_string_key() {
+        let mut trans_list = TransformableList::new(None);
+        trans_list.set("list.0".to_string(), evalexpr::Value::Int(0));
+        trans_list.set("list.name".to_string(), evalexpr::Value::Int(1));
+
+        let yml: Value = trans_list.try_into().unwrap();
+        let expected_yml: Value = serde_yaml::from_str(
+            r#"
+            list:
+                "0": 0
+                name: 1
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(yml, expected_yml);
+    }
+
+    #[test]
+    fn it_should_keep_null_values_apart_from_deleted_ones() {
+        let yml: Value = serde_yaml::from_str(
+            r#"
+            empty: null
+            copied: 1
+            deleted: 2
+            _transform:
+                - copied = empty
+                - delete("deleted")
+            "#,
+        )
+        .unwrap();
+        let mut trans_list = TransformableList::try_from(yml).unwrap();
+        trans_list.transform().unwrap();
+
+        let yml: Value = trans_list.try_into().unwrap();
+        let expected_yml: Value = serde_yaml::from_str(
+            r#"
+            empty: null
+            copied: null
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(yml, expected_yml);
+    }
 }
@@ -1,52 +1,65 @@
-use std::ops::{Deref, DerefMut};
+use std::cell::RefCell;
 
 use evalexpr::Value;
+use trace::Tracer;
 
-pub mod from_to_value;
-pub mod transformation;
+mod from_to_value;
+mod trace;
+mod transformation;
 
 #[derive(Clone, PartialEq, Debug)]
 pub struct TransformableList {
     list: Vec<(String, Value)>,
     operations: Option<Vec<String>>,
-}
-impl Deref for TransformableList {
-    type Target = Vec<(String, Value)>;
-
-    fn deref(&self) -> &Self::Target {
-        &self.list
-    }
-}
-impl DerefMut for TransformableList {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.list
-    }
+    tracer: Option<Tracer>,
+    /// Keys given to `delete` by the running formula, removed once it is evaluated.
+    deletions: RefCell<Vec<String>>,
 }
 impl TransformableList {
     pub fn new(operations: Option<Vec<String>>) -> Self {
         TransformableList {
             list: vec![],
             operations,
+            tracer: None,
+            deletions: RefCell::new(vec![]),
         }
     }
 
+    /// Sets a leaf, replacing any entry nested under `key` or any leaf `key` is nested under.
     fn set(&mut self, key: String, value: Value) {
-        let index = self.get_index(&key);
-        match index {
-            None => self.push((key, value)),
-            Some(index) => self.list[index] = (key, value),
+        match self.remove(&key) {
+            None => self.list.push((key, value)),
+            Some(index) => self.list.insert(index, (key, value)),
         }
     }
 
-    fn get(&self, key: &str) -> Option<&Value> {
-        self.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    /// Removes `key` with everything nested under it, returning where it was in the list.
+    fn remove(&mut self, key: &str) -> Option<usize> {
+        let index = self
+            .list
+            .iter()
+            .position(|(k, _)| k == key || is_nested(key, k) || is_nested(k, key));
+        self.list
+            .retain(|(k, _)| k != key && !is_nested(key, k) && !is_nested(k, key));
+        index
     }
 
-    fn get_index(&self, key: &str) -> Option<usize> {
-        self.iter().position(|(k, _)| k == key)
+    fn get(&self, key: &str) -> Option<&Value> {
+        self.list.iter().find(|(k, _)| k == key).map(|(_, v)| v)
     }
 
     fn get_operations(&self) -> Option<Vec<String>> {
         self.operations.clone()
     }
 }
+
+fn is_nested(parent: &str, key: &str) -> bool {
+    match parent {
+        "" => !key.is_empty(),
+        _ => {
+            key.len() > parent.len()
+                && key.starts_with(parent)
+                && key[parent.len()..].starts_with('.')
+        }
+    }
+}
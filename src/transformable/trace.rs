@@ -0,0 +1,78 @@
+use super::{from_to_value::value_convert, TransformableList};
+use crate::{adapters::TransformTrace, utils::result::AppResult};
+use evalexpr::Value;
+use std::collections::HashMap;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Tracer {
+    origins: HashMap<String, Vec<String>>,
+    /// How many times each formula was started.
+    runs: HashMap<String, usize>,
+    formula: Option<String>,
+    part: Option<String>,
+    traces: Vec<TransformTrace>,
+}
+
+impl TransformableList {
+    /// Records every assignment done by `transform`.
+    /// `origins` maps a formula to the parts declaring it, the nth run of a formula
+    /// being attributed to the nth part.
+    pub fn trace(&mut self, origins: HashMap<String, Vec<String>>) {
+        self.tracer = Some(Tracer {
+            origins,
+            runs: HashMap::new(),
+            formula: None,
+            part: None,
+            traces: vec![],
+        });
+    }
+
+    pub fn take_traces(&mut self) -> Vec<TransformTrace> {
+        match &mut self.tracer {
+            Some(tracer) => std::mem::take(&mut tracer.traces),
+            None => vec![],
+        }
+    }
+
+    pub(super) fn start_formula(&mut self, formula: &str) {
+        if let Some(tracer) = &mut self.tracer {
+            let run = tracer.runs.entry(formula.to_string()).or_default();
+            tracer.part = tracer
+                .origins
+                .get(formula)
+                .and_then(|parts| parts.get(*run).or(parts.last()))
+                .cloned();
+            *run += 1;
+            tracer.formula = Some(formula.to_string());
+        }
+    }
+
+    pub(super) fn record(&mut self, key: &str, new_value: &Value) -> AppResult<()> {
+        if self.tracer.is_none() {
+            return Ok(());
+        }
+
+        let old_value = match self.get(key) {
+            Some(value) => Some(value_convert(value)?),
+            None => None,
+        };
+
+        let tracer = match &mut self.tracer {
+            Some(tracer) => tracer,
+            None => return Ok(()),
+        };
+
+        let formula = tracer.formula.clone().unwrap_or_default();
+        let part = tracer.part.clone();
+
+        tracer.traces.push(TransformTrace {
+            formula,
+            key: key.to_string(),
+            old_value,
+            new_value: value_convert(new_value)?,
+            part,
+        });
+
+        Ok(())
+    }
+}
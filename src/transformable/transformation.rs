@@ -1,5 +1,6 @@
 use evalexpr::{
-    Context, ContextWithMutableVariables, EmptyContextWithBuiltinFunctions, EvalexprResult, Value,
+    Context, ContextWithMutableVariables, EmptyContextWithBuiltinFunctions, EvalexprError,
+    EvalexprResult, Value,
 };
 
 use crate::utils::result::{AppError, AppResult};
@@ -15,11 +16,17 @@ impl Context for TransformableList {
         Ok(())
     }
 
+    /// `delete("a.b")` removes a key and everything nested under it once the formula is
+    /// evaluated, other functions are the builtin ones.
     fn call_function(
         &self,
         _identifier: &str,
         _arg: &evalexpr::Value,
     ) -> EvalexprResult<evalexpr::Value> {
+        if _identifier == TransformableList::DELETE_FUNCTION {
+            self.deletions.borrow_mut().push(_arg.as_string()?);
+            return Ok(Value::Empty);
+        }
         let ctx = EmptyContextWithBuiltinFunctions {};
         ctx.call_function(_identifier, _arg)
     }
@@ -31,13 +38,16 @@ impl Context for TransformableList {
 
 impl ContextWithMutableVariables for TransformableList {
     fn set_value(&mut self, _identifier: String, _value: Value) -> EvalexprResult<()> {
-        println!("Setting {} to {:?}", _identifier, _value);
+        self.record(&_identifier, &_value)
+            .map_err(|e| EvalexprError::CustomMessage(e.to_string()))?;
         self.set(_identifier, _value);
         Ok(())
     }
 }
 
 impl TransformableList {
+    pub const DELETE_FUNCTION: &'static str = "delete";
+
     pub fn transform(&mut self) -> AppResult<&Self> {
         let operations = match self.get_operations() {
             Some(operations) => operations,
@@ -45,8 +55,13 @@ impl TransformableList {
         };
 
         for oper in operations {
+            self.start_formula(&oper);
             evalexpr::eval_with_context_mut(&oper, self)
                 .map_err(|e| AppError::ApplyFormula(e.to_string()))?;
+            for key in self.deletions.take() {
+                self.record(&key, &Value::Empty)?;
+                self.remove(&key);
+            }
         }
 
         Ok(self)
@@ -83,3 +98,119 @@ fn it_should_ceil() {
 
     assert_eq!(transf_list.get("var").unwrap(), &Value::Float(2.0));
 }
+
+#[test]
+fn it_should_trace_transformations() {
+    let operations = vec!["a = a + 1".to_string(), "b = a * 2".to_string()];
+
+    let mut transf_list = TransformableList::new(Some(operations));
+    transf_list.set("a".to_string(), Value::Float(1.0));
+    transf_list.trace(std::collections::HashMap::from([(
+        "a = a + 1".to_string(),
+        vec!["parts/a".to_string()],
+    )]));
+
+    transf_list.transform().unwrap();
+    let traces = transf_list.take_traces();
+
+    assert_eq!(traces.len(), 2);
+    assert_eq!(traces[0].formula, "a = a + 1");
+    assert_eq!(traces[0].key, "a");
+    assert_eq!(traces[0].old_value, Some(serde_yaml::Value::from(1)));
+    assert_eq!(traces[0].new_value, serde_yaml::Value::from(2));
+    assert_eq!(traces[0].part, Some("parts/a".to_string()));
+    assert_eq!(traces[1].key, "b");
+    assert_eq!(traces[1].old_value, None);
+    assert_eq!(traces[1].part, None);
+}
+
+#[test]
+fn it_should_attribute_identical_formulas_to_each_part() {
+    let operations = vec!["a = a + 1".to_string(), "a = a + 1".to_string()];
+
+    let mut transf_list = TransformableList::new(Some(operations));
+    transf_list.set("a".to_string(), Value::Float(1.0));
+    transf_list.trace(std::collections::HashMap::from([(
+        "a = a + 1".to_string(),
+        vec!["parts/a".to_string(), "parts/b".to_string()],
+    )]));
+
+    transf_list.transform().unwrap();
+    let traces = transf_list.take_traces();
+
+    assert_eq!(traces[0].part, Some("parts/a".to_string()));
+    assert_eq!(traces[1].part, Some("parts/b".to_string()));
+}
+
+#[test]
+fn it_should_not_trace_by_default() {
+    let mut transf_list = TransformableList::new(Some(vec!["a = 1".to_string()]));
+    transf_list.transform().unwrap();
+
+    assert!(transf_list.take_traces().is_empty());
+}
+
+#[test]
+fn it_should_keep_tuples_whole() {
+    let operations = vec!["a = (1, \"two\", (3, 4))".to_string(), "c = a".to_string()];
+
+    let mut transf_list = TransformableList::new(Some(operations));
+    transf_list.set("a.0".to_string(), Value::Float(0.0));
+    transf_list.set("a.1".to_string(), Value::Float(0.0));
+    transf_list.set("a.2".to_string(), Value::Float(0.0));
+    transf_list.set("a.3".to_string(), Value::Float(0.0));
+    transf_list.set("b".to_string(), Value::Float(3.0));
+
+    transf_list.transform().unwrap();
+
+    let tuple = Value::Tuple(vec![
+        Value::Int(1),
+        Value::String("two".to_string()),
+        Value::Tuple(vec![Value::Int(3), Value::Int(4)]),
+    ]);
+    assert_eq!(
+        transf_list.list,
+        vec![
+            ("a".to_string(), tuple.clone()),
+            ("b".to_string(), Value::Float(3.0)),
+            ("c".to_string(), tuple),
+        ]
+    );
+}
+
+#[test]
+fn it_should_delete_keys_given_to_delete() {
+    let operations = vec!["delete(\"a\")".to_string(), "delete(\"b.x\")".to_string()];
+
+    let mut transf_list = TransformableList::new(Some(operations));
+    transf_list.set("a.0.u".to_string(), Value::Float(1.0));
+    transf_list.set("a.1.u".to_string(), Value::Float(2.0));
+    transf_list.set("b.x".to_string(), Value::Float(3.0));
+    transf_list.set("b.y".to_string(), Value::Float(4.0));
+
+    transf_list.transform().unwrap();
+
+    assert_eq!(
+        transf_list.list,
+        vec![("b.y".to_string(), Value::Float(4.0))]
+    );
+}
+
+#[test]
+fn it_should_replace_leaf_by_nested_key() {
+    let operations = vec!["a.b = 2".to_string()];
+
+    let mut transf_list = TransformableList::new(Some(operations));
+    transf_list.set("a".to_string(), Value::Float(1.0));
+    transf_list.set("c".to_string(), Value::Float(3.0));
+
+    transf_list.transform().unwrap();
+
+    assert_eq!(
+        transf_list.list,
+        vec![
+            ("a.b".to_string(), Value::Int(2)),
+            ("c".to_string(), Value::Float(3.0)),
+        ]
+    );
+}
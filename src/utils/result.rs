@@ -4,6 +4,12 @@ pub enum AppError {
     Other(#[from] anyhow::Error),
     #[error("{0}")]
     FileSystem(String),
+    #[error("Could not find part {0}")]
+    MissingPart(String),
+    #[error("Forbidden path: {0}")]
+    ForbiddenPath(String),
+    #[error("Include cycle: {0}")]
+    IncludeCycle(String),
     #[error("{0}")]
     ParseYml(String),
     #[error("{0}")]
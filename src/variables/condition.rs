@@ -0,0 +1,228 @@
+use super::Variables;
+use crate::{
+    aggregator::YmlAggregator,
+    utils::result::{AppError, AppResult},
+};
+use serde_yaml::{
+    value::{Tag, TaggedValue},
+    Mapping, Value,
+};
+
+impl Variables {
+    const IF_TAG: &'static str = "!if";
+    const IF_TAG_PREFIX: &'static str = "!if::";
+    pub(crate) const INCLUDE_IF_TAG_PREFIX: &'static str = "!inc-if::";
+
+    /// Resolves `!if::<cond>`, `!if { cond, then, else }` and `!inc-if::<cond>::<part>`.
+    /// Values whose condition is false are removed from their mapping or sequence.
+    pub fn resolve_conditions(&self, val: &Value) -> AppResult<Value> {
+        Ok(self.on_condition(val)?.unwrap_or(Value::Null))
+    }
+
+    pub fn evaluate_condition(&self, condition: &Value) -> AppResult<bool> {
+        let evaluated = match condition {
+            Value::String(str) => match self.on_string(str)? {
+                Value::String(str) => self.evaluate_string(&str)?,
+                value => value,
+            },
+            value => value.clone(),
+        };
+
+        match evaluated {
+            Value::Bool(b) => Ok(b),
+            Value::Null => Ok(false),
+            Value::String(str) if str.is_empty() => Ok(false),
+            _ => Err(AppError::ParseYml(format!(
+                "Condition {condition:?} does not evaluate to a boolean"
+            ))),
+        }
+    }
+
+    fn on_condition(&self, val: &Value) -> AppResult<Option<Value>> {
+        match val {
+            Value::Tagged(t) => self.on_conditional_tag(t),
+            Value::Mapping(map) => {
+                let mut new_map = Mapping::new();
+                for (key, value) in map {
+                    if let Some(value) = self.on_condition(value)? {
+                        new_map.insert(key.clone(), value);
+                    }
+                }
+                Ok(Some(Value::Mapping(new_map)))
+            }
+            Value::Sequence(seq) => {
+                let mut new_seq: Vec<Value> = vec![];
+                for value in seq {
+                    if let Some(value) = self.on_condition(value)? {
+                        new_seq.push(value);
+                    }
+                }
+                Ok(Some(Value::Sequence(new_seq)))
+            }
+            x => Ok(Some(x.clone())),
+        }
+    }
+
+    fn on_conditional_tag(&self, val: &TaggedValue) -> AppResult<Option<Value>> {
+        let tag = val.tag.to_string();
+
+        // Loop bodies may depend on the loop variables, they are resolved once per iteration.
+        if tag == YmlAggregator::EACH_TAG {
+            return Ok(Some(Value::Tagged(Box::new(val.clone()))));
+        }
+
+        if tag == Self::IF_TAG {
+            let map = val.value.as_mapping().ok_or_else(|| {
+                AppError::ParseYml(format!("{tag} expects a mapping with cond, then and else"))
+            })?;
+            let condition = map
+                .get("cond")
+                .ok_or_else(|| AppError::ParseYml(format!("{tag} is missing its cond")))?;
+
+            let branch = match self.evaluate_condition(condition)? {
+                true => map.get("then"),
+                false => map.get("else"),
+            };
+
+            return match branch {
+                Some(branch) => self.on_condition(branch),
+                None => Ok(None),
+            };
+        }
+
+        if let Some(condition) = tag.strip_prefix(Self::IF_TAG_PREFIX) {
+            return match self.evaluate_condition(&Value::String(condition.to_string()))? {
+                true => self.on_condition(&val.value),
+                false => Ok(None),
+            };
+        }
+
+        if let Some(conditional_include) = tag.strip_prefix(Self::INCLUDE_IF_TAG_PREFIX) {
+            let (condition, part) = conditional_include.split_once("::").ok_or_else(|| {
+                AppError::ParseYml(format!(
+                    "{tag} should be written {}<condition>::<part>",
+                    Self::INCLUDE_IF_TAG_PREFIX
+                ))
+            })?;
+
+            return match self.evaluate_condition(&Value::String(condition.to_string()))? {
+                true => Ok(Some(Value::Tagged(Box::new(TaggedValue {
+                    tag: Tag::new(format!("{}{part}", YmlAggregator::INCLUDE_TAG_PREFIX)),
+                    value: self.resolve_conditions(&val.value)?,
+                })))),
+                false => Ok(None),
+            };
+        }
+
+        Ok(Some(Value::Tagged(Box::new(TaggedValue {
+            tag: Tag::new(tag),
+            value: self.resolve_conditions(&val.value)?,
+        }))))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get_variables() -> Variables {
+        let variables: Value = serde_yaml::from_str(
+            r#"
+            ADULT: "true"
+            EDITION: children
+            NOTHING: null
+            "#,
+        )
+        .unwrap();
+        variables.try_into().unwrap()
+    }
+
+    #[test]
+    fn it_should_drop_keys_and_items_on_false_condition() {
+        let variables = get_variables();
+        let yml_part: Value = serde_yaml::from_str(
+            r#"
+            warning: !if::$ADULT Contains violence
+            nothing: !if::$NOTHING Never there
+            tags:
+                - !if::!$ADULT childhood
+                - !if::$ADULT adult
+            "#,
+        )
+        .unwrap();
+        let yml_part = variables.inject(&yml_part).unwrap();
+        let yml = variables.resolve_conditions(&yml_part).unwrap();
+
+        let expected_yml: Value = serde_yaml::from_str(
+            r#"
+            warning: Contains violence
+            tags:
+                - adult
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(yml, expected_yml);
+    }
+
+    #[test]
+    fn it_should_pick_branch_of_if_mapping() {
+        let variables = get_variables();
+        let yml_part: Value = serde_yaml::from_str(
+            r#"
+            summary: !if
+                cond: '"$EDITION" == "children"'
+                then: A gentle story
+                else: A dark story
+            epilogue: !if
+                cond: '"$EDITION" == "adult"'
+                then: Everybody dies
+            "#,
+        )
+        .unwrap();
+        let yml_part = variables.inject(&yml_part).unwrap();
+        let yml = variables.resolve_conditions(&yml_part).unwrap();
+
+        let expected_yml: Value = serde_yaml::from_str(
+            r#"
+            summary: A gentle story
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(yml, expected_yml);
+    }
+
+    #[test]
+    fn it_should_turn_conditional_include_into_include() {
+        let variables = get_variables();
+        let yml_part: Value = serde_yaml::from_str(
+            r#"
+            - !inc-if::$ADULT::tags/adult
+                size: 2
+            - !inc-if::!$ADULT::tags/childhood
+            "#,
+        )
+        .unwrap();
+        let yml_part = variables.inject(&yml_part).unwrap();
+        let yml = variables.resolve_conditions(&yml_part).unwrap();
+
+        let expected_yml: Value = serde_yaml::from_str(
+            r#"
+            - !inc::tags/adult
+                size: 2
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(yml, expected_yml);
+    }
+
+    #[test]
+    fn it_should_fail_on_non_boolean_condition() {
+        let variables = get_variables();
+        let condition = Value::String("$EDITION".to_string());
+
+        assert!(variables.evaluate_condition(&condition).is_err());
+    }
+}
@@ -13,7 +13,9 @@ impl TryInto<Variables> for Value {
                 for (key, value) in map {
                     let key = match key {
                         Value::String(str) => Ok(str),
-                        _ => Err(AppError::ParseYml(format!("Variable key is not a string"))),
+                        _ => Err(AppError::ParseYml(
+                            "Variable key is not a string".to_string(),
+                        )),
                     }?;
 
                     variables.insert(key, value);
@@ -21,7 +23,7 @@ impl TryInto<Variables> for Value {
                 Ok(variables)
             }
             Value::Null => Ok(variables),
-            _ => Err(AppError::ParseYml(format!("Cannot parse as variables"))),
+            _ => Err(AppError::ParseYml("Cannot parse as variables".to_string())),
         }
     }
 }
@@ -1,5 +1,8 @@
 use super::Variables;
-use crate::utils::result::{AppError, AppResult};
+use crate::{
+    aggregator::YmlAggregator,
+    utils::result::{AppError, AppResult},
+};
 use evalexpr::eval;
 use regex::Regex;
 use serde_yaml::{
@@ -21,6 +24,11 @@ impl Variables {
 
     fn on_tag(&self, val: &TaggedValue) -> AppResult<Value> {
         let tag_label = &val.tag.to_string();
+
+        // Loop variables win over outer ones, loops are injected once per iteration.
+        if tag_label == YmlAggregator::EACH_TAG {
+            return Ok(Value::Tagged(Box::new(val.clone())));
+        }
         let tag = self.on_string(tag_label)?;
 
         let tag = match tag {
@@ -37,7 +45,7 @@ impl Variables {
     fn on_sequence(&self, val: &Vec<Value>) -> AppResult<Value> {
         let mut new_seq: Vec<Value> = vec![];
         for value in val {
-            let yml = self.inject(&value)?;
+            let yml = self.inject(value)?;
             new_seq.push(yml)
         }
         Ok(Value::Sequence(new_seq))
@@ -61,15 +69,31 @@ impl Variables {
                 )))?,
             };
 
-            let yml = self.inject(&value)?;
+            let yml = self.inject(value)?;
             new_map.insert(Value::String(new_key), yml);
         }
         Ok(Value::Mapping(new_map))
     }
 
-    fn on_string(&self, str: &str) -> AppResult<Value> {
+    pub(super) fn on_string(&self, str: &str) -> AppResult<Value> {
+        // Evaluated once every variable is replaced, so that an expression
+        // is never computed while some of its variables are still missing.
+        match self.replace_variables(str)? {
+            (Value::String(str), true) => self.evaluate_string(&str),
+            (val, _) => Ok(val),
+        }
+    }
+
+    /// `str` with its variables replaced but not evaluated, like a path.
+    pub(crate) fn substitute(&self, str: &str) -> AppResult<Value> {
+        Ok(self.replace_variables(str)?.0)
+    }
+
+    /// `str` with its variables replaced, and whether it is to be evaluated as a string.
+    fn replace_variables(&self, str: &str) -> AppResult<(Value, bool)> {
         let mut val = Value::String(str.to_string());
         let mut is_replacing = true;
+        let mut is_evaluated = false;
 
         while is_replacing {
             let folded: AppResult<(Value, bool)> = self.iter().try_fold(
@@ -79,9 +103,7 @@ impl Variables {
                     let is_standalone = acc == Value::String(variable_identifier.clone());
 
                     let new_acc = match (is_standalone, acc.clone(), var_value) {
-                        (is_standalone, _, var_value) if is_standalone == true => {
-                            Ok(var_value.clone())
-                        }
+                        (is_standalone, _, var_value) if is_standalone => Ok(var_value.clone()),
                         (_, Value::String(acc_string), var_value) => {
                             let var_value = match var_value {
                                 Value::String(str) => str.to_string(),
@@ -93,13 +115,13 @@ impl Variables {
                                 .map_err(|e| {
                                     AppError::ParseYml(format!(
                                         "{var_key} can't be used as variable identifier: {}",
-                                        e.to_string()
+                                        e
                                     ))
                                 })?;
-                            let acc_string: String =
+                            let new_acc_string: String =
                                 regex.replace_all(&acc_string, var_value).to_string();
-                            let acc_string = self.evaluate_string(&acc_string)?;
-                            Ok(acc_string)
+                            is_evaluated = true;
+                            Ok(Value::String(new_acc_string))
                         }
                         (_, acc, _) => self.inject(&acc),
                     }?;
@@ -117,10 +139,10 @@ impl Variables {
             is_replacing = folded.1;
         }
 
-        Ok(val)
+        Ok((val, is_evaluated))
     }
 
-    fn evaluate_string(&self, str: &str) -> AppResult<Value> {
+    pub(super) fn evaluate_string(&self, str: &str) -> AppResult<Value> {
         let str = str.to_string();
         fn contains_multibyte(s: &str) -> bool {
             for c in s.chars() {
@@ -165,7 +187,7 @@ mod test {
     use super::*;
 
     fn get_yml_variables() -> &'static str {
-        let yml_part = r#"
+        r#"
             test: 1
             test2: 10.1
             a: Something
@@ -176,8 +198,7 @@ mod test {
                 foo: foo_string
                 bar: false
             d: null
-        "#;
-        yml_part
+        "#
     }
 
     #[test]
@@ -258,4 +279,37 @@ mod test {
             _ => panic!("yml should be a mapping"),
         };
     }
+
+    #[test]
+    fn it_should_evaluate_strings_as_soon_as_variables_are_given() {
+        let variables: Value = serde_yaml::from_str("formula: 2 * 3").unwrap();
+        let variables: Variables = variables.try_into().unwrap();
+
+        let yml_part: Value = serde_yaml::from_str(
+            r#"
+            - 1 + 2
+            - $formula
+            - Juliette
+        "#,
+        )
+        .unwrap();
+        let yml = variables.inject(&yml_part).unwrap();
+
+        let expected_yml: Value = serde_yaml::from_str("[3, 6, Juliette]").unwrap();
+        assert_eq!(yml, expected_yml);
+        assert_eq!(Variables::new().inject(&yml_part).unwrap(), yml_part);
+    }
+
+    #[test]
+    fn it_should_evaluate_expressions_once_all_variables_are_replaced() {
+        let mut variables = Variables::new();
+        variables.insert("ADULT".to_string(), Value::Bool(false));
+        variables.insert("EDITION".to_string(), Value::from("children"));
+
+        let yml = variables
+            .inject(&Value::from(r#""$EDITION" == "children""#))
+            .unwrap();
+
+        assert_eq!(yml, Value::Bool(true));
+    }
 }
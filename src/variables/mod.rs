@@ -1,29 +1,33 @@
 use serde_yaml::Value;
-use std::{
-    collections::HashMap,
-    ops::{Deref, DerefMut},
-};
+use std::collections::HashMap;
 
+mod condition;
 mod from_value;
 mod inject;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Variables(HashMap<String, Value>);
-impl Deref for Variables {
-    type Target = HashMap<String, Value>;
-    fn deref(&self) -> &Self::Target {
-        &self.0
-    }
-}
-impl DerefMut for Variables {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
-    }
-}
 impl Variables {
     pub fn new() -> Self {
         Self(HashMap::new())
     }
+
+    /// Sets `key`, replacing its previous value.
+    pub fn insert(&mut self, key: String, value: Value) {
+        self.0.insert(key, value);
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.0.get(key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.0.iter()
+    }
+
+    pub(crate) fn remove(&mut self, key: &str) -> Option<Value> {
+        self.0.remove(key)
+    }
 }
 
 impl From<HashMap<String, String>> for Variables {
@@ -0,0 +1,62 @@
+use serde_yaml::Value;
+use std::{path::PathBuf, sync::Arc};
+use yml_assembler::{
+    adapters::AssemblyOutputFormat,
+    lib_infras::assembly_in_memory_output::AssemblyIMOutput,
+    options::{AssembleOptions, EvaluationMode, MergePolicy},
+    utils::result::AppError,
+    App,
+};
+
+fn root() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/yml_test_files")
+}
+
+#[tokio::test]
+async fn it_should_build_an_app_reading_from_root() {
+    let assembly_output = Arc::new(AssemblyIMOutput::new());
+    let app = App::builder(root())
+        .with_assembly_output(assembly_output.clone())
+        .build();
+
+    app.compile_and_validate_yml("optional_include", None, None, &AssemblyOutputFormat::Yml)
+        .unwrap();
+
+    let yml = assembly_output.get_yml_output().unwrap();
+    assert_eq!(
+        yml.get("optional_include").unwrap()["title"],
+        Value::from("Standard edition")
+    );
+}
+
+#[tokio::test]
+async fn it_should_fail_on_warnings_when_strict() {
+    let app = App::builder(root())
+        .with_options(AssembleOptions::default().with_strict(true))
+        .build();
+
+    let result = app.assemble("optional_include", None, None);
+
+    assert!(matches!(
+        result,
+        Err(AppError::ValidateYml(message)) if message.contains("overrides/local")
+    ));
+}
+
+#[tokio::test]
+async fn it_should_replace_mixed_values_and_skip_formulas() {
+    let app = App::builder(root()).build();
+    let options = AssembleOptions::default()
+        .with_merge(MergePolicy::Replace)
+        .with_evaluation(EvaluationMode::Skip)
+        .with_log(false);
+
+    let assembly = app
+        .assemble_with("simple_book", None, None, &options)
+        .unwrap();
+
+    let covers: Value = serde_yaml::from_str("- color: rose\n  size: 15").unwrap();
+    assert_eq!(assembly.value["covers"], covers);
+    assert!(assembly.value.get("_transform").is_some());
+    assert!(assembly.value["page"].get("weight").is_none());
+}
@@ -0,0 +1,249 @@
+use assert_cmd::prelude::{CommandCargoExt, OutputAssertExt};
+use serde_yaml::Value;
+use serial_test::serial;
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Arc,
+};
+use yml_assembler::{
+    adapters::{AssemblyOutputFormat, PartReaderPort, SchemaReaderPort},
+    lib_infras::{
+        assembly_in_memory_output::AssemblyIMOutput,
+        assembly_part_archive_reader::PartArchiveReader,
+        schema_archive_reader::SchemaArchiveReader, schema_in_memory_output::SchemaIMOutput,
+    },
+    utils::result::AppError,
+    App,
+};
+
+static ARCHIVE_DIR: &str = "./tests/yml_test_files/archive_output";
+
+static FILES: [(&str, &str); 4] = [
+    ("book.pyml", "title: Archived book\ncast: !inc::tags/*\n"),
+    ("tags/hero.pyml", "name: Hero\n"),
+    ("tags/villain.pyml", "name: Villain\n"),
+    ("book-schema.yml", "type: object\nrequired:\n  - title\n"),
+];
+
+fn write_zip(path: &Path) {
+    let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+    for (name, content) in FILES {
+        zip.start_file(name, zip::write::FileOptions::default())
+            .unwrap();
+        zip.write_all(content.as_bytes()).unwrap();
+    }
+    zip.finish().unwrap();
+}
+
+fn write_tar_gz(path: &Path) {
+    let encoder =
+        flate2::write::GzEncoder::new(File::create(path).unwrap(), flate2::Compression::default());
+    let mut tar = tar::Builder::new(encoder);
+    for (name, content) in FILES {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, format!("./{name}"), content.as_bytes())
+            .unwrap();
+    }
+    tar.into_inner().unwrap().finish().unwrap();
+}
+
+fn assemble_from(archive: &Path) -> Value {
+    let assembly_output = Arc::new(AssemblyIMOutput::new());
+    let app = App::new(
+        Arc::new(PartArchiveReader::open(archive).unwrap()),
+        Arc::new(SchemaArchiveReader::open(archive).unwrap()),
+        assembly_output.clone(),
+        Arc::new(SchemaIMOutput::new()),
+    );
+
+    app.compile_and_validate_yml(
+        "book",
+        Some("book-schema.yml"),
+        None,
+        &AssemblyOutputFormat::Yml,
+    )
+    .unwrap();
+    let yml = assembly_output.get_yml_output().unwrap();
+    yml.get("book").unwrap().clone()
+}
+
+fn expected_book() -> Value {
+    serde_yaml::from_str(
+        r#"
+        title: Archived book
+        cast:
+          - name: Hero
+          - name: Villain
+        "#,
+    )
+    .unwrap()
+}
+
+#[test]
+#[serial]
+fn it_should_assemble_from_zip_and_tar_gz_archives() {
+    fs::create_dir_all(ARCHIVE_DIR).unwrap();
+    let zip_path = PathBuf::from(ARCHIVE_DIR).join("book.zip");
+    let tar_path = PathBuf::from(ARCHIVE_DIR).join("book.tar.gz");
+    write_zip(&zip_path);
+    write_tar_gz(&tar_path);
+
+    assert_eq!(assemble_from(&zip_path), expected_book());
+    assert_eq!(assemble_from(&tar_path), expected_book());
+
+    fs::remove_dir_all(ARCHIVE_DIR).unwrap();
+}
+
+#[test]
+#[serial]
+fn it_should_accept_an_archive_root_from_cli() {
+    fs::create_dir_all(ARCHIVE_DIR).unwrap();
+    let zip_path = PathBuf::from(ARCHIVE_DIR).join("book.zip");
+    let output = PathBuf::from(ARCHIVE_DIR).join("output");
+    write_zip(&zip_path);
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.arg("-r").arg(&zip_path);
+    cmd.arg("-e").arg("book");
+    cmd.arg("-s").arg("book-schema.yml");
+    cmd.arg("-o").arg(&output);
+
+    cmd.assert().success();
+
+    let assembled_file = fs::read_to_string(output.join("book.yml")).unwrap();
+    assert!(assembled_file.contains("title: Archived book"));
+    assert!(assembled_file.contains("name: Villain"));
+
+    fs::remove_dir_all(ARCHIVE_DIR).unwrap();
+}
+
+fn write_zip_files(path: &Path, files: &[(&str, &[u8])]) {
+    let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+    for (name, content) in files {
+        zip.start_file(*name, zip::write::FileOptions::default())
+            .unwrap();
+        zip.write_all(content).unwrap();
+    }
+    zip.finish().unwrap();
+}
+
+#[test]
+#[serial]
+fn it_should_read_archived_parts_with_the_configured_extensions() {
+    fs::create_dir_all(ARCHIVE_DIR).unwrap();
+    let zip_path = PathBuf::from(ARCHIVE_DIR).join("parts.zip");
+    write_zip_files(
+        &zip_path,
+        &[
+            ("book.yml", b"title: Yml book"),
+            ("tags/hero.yml", b"name: Hero"),
+            ("tags/villain.pyml", b"name: Villain"),
+        ],
+    );
+
+    let reader = PartArchiveReader::open(&zip_path)
+        .unwrap()
+        .with_extensions(vec!["yml".to_string()]);
+    assert_eq!(
+        reader.get_value("book").unwrap()["title"],
+        Value::from("Yml book")
+    );
+    assert_eq!(
+        reader.get_filepathes_from_glob("tags/*").unwrap(),
+        vec!["tags/hero"]
+    );
+    assert!(matches!(
+        reader.get_value("tags/villain"),
+        Err(AppError::MissingPart(_))
+    ));
+
+    fs::remove_dir_all(ARCHIVE_DIR).unwrap();
+}
+
+#[test]
+#[serial]
+fn it_should_fail_on_parts_that_are_not_utf8() {
+    fs::create_dir_all(ARCHIVE_DIR).unwrap();
+    let zip_path = PathBuf::from(ARCHIVE_DIR).join("binary.zip");
+    write_zip_files(&zip_path, &[("binary.pyml", &[0xff, 0xfe, 0x00])]);
+
+    let reader = PartArchiveReader::open(&zip_path).unwrap();
+    assert!(matches!(
+        reader.get_value("binary"),
+        Err(AppError::FileSystem(message)) if message.contains("not valid UTF-8")
+    ));
+
+    fs::remove_dir_all(ARCHIVE_DIR).unwrap();
+}
+
+#[test]
+#[serial]
+fn it_should_keep_parts_inside_the_archive_unless_allowed() {
+    fs::create_dir_all(ARCHIVE_DIR).unwrap();
+    let zip_path = PathBuf::from(ARCHIVE_DIR).join("book.zip");
+    write_zip(&zip_path);
+    fs::write(
+        PathBuf::from(ARCHIVE_DIR).join("outside.pyml"),
+        "name: Outside",
+    )
+    .unwrap();
+    let outside = "../archive_output/outside";
+
+    let reader = PartArchiveReader::open(&zip_path).unwrap();
+    assert!(matches!(
+        reader.get_value(outside),
+        Err(AppError::ForbiddenPath(_))
+    ));
+
+    let reader = PartArchiveReader::open(&zip_path)
+        .unwrap()
+        .allow_outside_roots();
+    assert_eq!(
+        reader.get_value(outside).unwrap()["name"],
+        Value::from("Outside")
+    );
+
+    fs::remove_dir_all(ARCHIVE_DIR).unwrap();
+}
+
+#[test]
+#[serial]
+fn it_should_keep_schemas_inside_the_archive_unless_allowed() {
+    fs::create_dir_all(ARCHIVE_DIR).unwrap();
+    let zip_path = PathBuf::from(ARCHIVE_DIR).join("book.zip");
+    write_zip(&zip_path);
+    fs::write(
+        PathBuf::from(ARCHIVE_DIR).join("outside-schema.yml"),
+        "type: string\n",
+    )
+    .unwrap();
+    let outside = "../archive_output/outside-schema.yml";
+
+    let reader = SchemaArchiveReader::open(&zip_path).unwrap();
+    assert_eq!(
+        reader
+            .get_validation_schema("tags/../book-schema.yml")
+            .unwrap()["type"],
+        "object"
+    );
+    assert!(matches!(
+        reader.get_validation_schema(outside),
+        Err(AppError::ForbiddenPath(_))
+    ));
+
+    let reader = SchemaArchiveReader::open(&zip_path)
+        .unwrap()
+        .allow_outside_roots();
+    assert_eq!(
+        reader.get_validation_schema(outside).unwrap()["type"],
+        "string"
+    );
+
+    fs::remove_dir_all(ARCHIVE_DIR).unwrap();
+}
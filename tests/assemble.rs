@@ -0,0 +1,48 @@
+use std::collections::HashMap;
+
+pub mod test_infra;
+
+#[tokio::test]
+async fn it_should_return_the_assembly_without_outputting_it() {
+    let (app, assembly_output, schema_output) = test_infra::get_test_app();
+    let variables = HashMap::from([
+        ("META".to_string(), "I'm a root variable".to_string()),
+        ("META2".to_string(), "I'm another variable".to_string()),
+    ]);
+
+    let assembly = app
+        .assemble("simple_book", Some("book-schema.json"), Some(variables))
+        .unwrap();
+
+    assert_eq!(
+        assembly.value["page"]["weight"],
+        serde_yaml::Value::from(10)
+    );
+    assert!(assembly.schema.is_some());
+    assert!(assembly.warnings.is_empty());
+    assert_eq!(assembly.metadata.entry, "simple_book");
+    assert_eq!(
+        assembly.metadata.schema_id.as_deref(),
+        Some("book-schema.json")
+    );
+    assert!(assembly
+        .metadata
+        .dependencies
+        .parts
+        .contains("stories/birthday"));
+    assert!(!assembly.metadata.cached);
+
+    assert!(assembly_output.get_yml_output().unwrap().is_empty());
+    assert!(schema_output.get_output().unwrap().is_none());
+}
+
+#[tokio::test]
+async fn it_should_warn_about_missing_optional_parts() {
+    let (app, _, _) = test_infra::get_test_app();
+    let assembly = app.assemble("optional_include", None, None).unwrap();
+
+    assert_eq!(
+        assembly.warnings,
+        vec!["Optional part overrides/local included by optional_include not found"]
+    );
+}
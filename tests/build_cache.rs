@@ -0,0 +1,98 @@
+use assert_cmd::prelude::{CommandCargoExt, OutputAssertExt};
+use predicates::prelude::{predicate, PredicateBooleanExt};
+use serde_yaml::Value;
+use serial_test::serial;
+use std::{collections::HashMap, fs, path::PathBuf, process::Command, sync::Arc};
+use yml_assembler::{
+    adapters::{AssemblyOutputFormat, BuildCachePort},
+    lib_infras::{
+        assembly_in_memory_output::AssemblyIMOutput, assembly_part_in_memory_reader::PartIMReader,
+        build_cache_fs::BuildCacheFS, schema_fs_reader::SchemaFSReader,
+        schema_in_memory_output::SchemaIMOutput,
+    },
+    App,
+};
+
+static CACHE_DIR: &str = "./tests/yml_test_files/build_cache_output";
+
+#[test]
+#[serial]
+fn it_should_reuse_the_record_until_an_input_changes() {
+    let reader = Arc::new(
+        PartIMReader::from_strings(HashMap::from([
+            (
+                "book".to_string(),
+                "title: $TITLE\nhero: !inc::hero".to_string(),
+            ),
+            ("hero".to_string(), "name: Juliette".to_string()),
+        ]))
+        .unwrap(),
+    );
+    let build_cache = Arc::new(BuildCacheFS::new(PathBuf::from(CACHE_DIR)));
+    let assembly_output = Arc::new(AssemblyIMOutput::new());
+    let app = App::new(
+        reader.clone(),
+        Arc::new(SchemaFSReader::new(PathBuf::from(env!(
+            "CARGO_MANIFEST_DIR"
+        )))),
+        assembly_output.clone(),
+        Arc::new(SchemaIMOutput::new()),
+    )
+    .with_build_cache(build_cache.clone());
+
+    let assemble = |title: &str| {
+        let variables = HashMap::from([("TITLE".to_string(), title.to_string())]);
+        app.compile_and_validate_yml("book", None, Some(variables), &AssemblyOutputFormat::Yml)
+            .unwrap();
+        assembly_output.get_yml_output().unwrap()["book"].clone()
+    };
+
+    let book = assemble("Cake");
+    assert_eq!(book["hero"]["name"], Value::from("Juliette"));
+
+    let mut record = build_cache.get("book").unwrap().unwrap();
+    record.output = Value::from("from cache");
+    build_cache.set("book", &record).unwrap();
+    assert_eq!(assemble("Cake"), Value::from("from cache"));
+
+    assert_eq!(assemble("Crash")["title"], Value::from("Crash"));
+
+    reader
+        .insert_part("hero", serde_yaml::from_str("name: Romeo").unwrap())
+        .unwrap();
+    assert_eq!(assemble("Crash")["hero"]["name"], Value::from("Romeo"));
+
+    fs::remove_dir_all(CACHE_DIR).unwrap();
+}
+
+#[test]
+#[serial]
+fn it_should_skip_unchanged_entries_from_cli() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/yml_test_files");
+    let output = PathBuf::from(CACHE_DIR).join("output");
+    let run = || {
+        let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+        cmd.arg("-r").arg(&root);
+        cmd.arg("-e").arg("simple_book");
+        cmd.arg("-s").arg("book-schema.yml");
+        cmd.arg("-o").arg(&output);
+        cmd.arg("--cache-dir")
+            .arg(PathBuf::from(CACHE_DIR).join("cache"));
+        cmd
+    };
+
+    run()
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("up to date").not());
+    fs::remove_dir_all(&output).unwrap();
+    run()
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("up to date: simple_book"));
+
+    let assembled_file = fs::read_to_string(output.join("simple_book.yml")).unwrap();
+    assert!(assembled_file.contains("title: Juliette coupe le gateau"));
+
+    fs::remove_dir_all(CACHE_DIR).unwrap();
+}
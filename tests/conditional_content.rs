@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+use yml_assembler::adapters::AssemblyOutputFormat;
+
+pub mod test_infra;
+
+#[derive(Debug, serde::Deserialize)]
+struct EditionFromYml {
+    summary: String,
+    warning: Option<String>,
+    tags: Vec<String>,
+    covers: Option<Vec<CoverFromYml>>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct CoverFromYml {
+    color: String,
+}
+
+static TEST_FILE: &str = "edition_book";
+
+fn assemble_edition(adult: &str, edition: &str) -> EditionFromYml {
+    let mut variables = HashMap::new();
+    variables.insert("ADULT".to_string(), adult.to_string());
+    variables.insert("EDITION".to_string(), edition.to_string());
+
+    let (app, assembly_output, _) = test_infra::get_test_app();
+    app.compile_and_validate_yml(TEST_FILE, None, Some(variables), &AssemblyOutputFormat::Yml)
+        .unwrap();
+    let yml = assembly_output
+        .get_yml_output()
+        .unwrap()
+        .get(TEST_FILE)
+        .unwrap()
+        .clone();
+
+    serde_yaml::from_value(yml).unwrap()
+}
+
+#[tokio::test]
+async fn it_should_assemble_adult_edition() {
+    let book = assemble_edition("true", "adult");
+
+    assert_eq!(book.summary, "L'anniversaire de Juliette tourne mal");
+    assert_eq!(book.warning, Some("Contains violence".to_string()));
+    assert_eq!(book.tags, vec!["childhood", "adult"]);
+    assert_eq!(book.covers.unwrap()[0].color, "rose");
+}
+
+#[tokio::test]
+async fn it_should_assemble_children_edition() {
+    let book = assemble_edition("false", "children");
+
+    assert_eq!(book.summary, "Juliette fête son anniversaire");
+    assert_eq!(book.warning, None);
+    assert_eq!(book.tags, vec!["childhood"]);
+    assert!(book.covers.is_none());
+}
@@ -0,0 +1,153 @@
+use assert_cmd::prelude::{CommandCargoExt, OutputAssertExt};
+use predicates::prelude::predicate;
+use serial_test::serial;
+use std::{collections::HashMap, path::PathBuf, process::Command, sync::Arc};
+use yml_assembler::{
+    explain::Provenance,
+    lib_infras::assembly_part_in_memory_reader::PartIMReader,
+    options::{AssembleOptions, MergePolicy},
+    App,
+};
+
+pub mod test_infra;
+
+fn variables() -> Option<HashMap<String, String>> {
+    Some(HashMap::from([
+        ("META".to_string(), "I'm a root variable".to_string()),
+        ("META2".to_string(), "I'm another variable".to_string()),
+    ]))
+}
+
+#[tokio::test]
+async fn it_should_explain_a_mixed_value() {
+    let (app, _, _) = test_infra::get_test_app();
+    let explanation = app
+        .explain("simple_book", "covers.2.color", variables())
+        .unwrap();
+
+    assert_eq!(explanation.value, Some("black".into()));
+    assert_eq!(
+        explanation.steps,
+        vec![
+            Provenance::Entry {
+                part: "simple_book".to_string()
+            },
+            Provenance::Include {
+                from: "simple_book".to_string(),
+                part: "tags/horror".to_string()
+            },
+            Provenance::Mixin {
+                part: "tags/horror".to_string(),
+                key: "covers".to_string()
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn it_should_explain_included_and_transformed_values() {
+    let (app, _, _) = test_infra::get_test_app();
+
+    let content = app
+        .explain("simple_book", "story.content", variables())
+        .unwrap();
+    assert_eq!(
+        content.steps[1],
+        Provenance::Include {
+            from: "simple_book".to_string(),
+            part: "stories/birthday".to_string()
+        }
+    );
+    assert!(matches!(
+        &content.steps[2],
+        Provenance::Variable { part, expression }
+            if part == "stories/birthday" && expression.contains("$name")
+    ));
+
+    let weight = app
+        .explain("simple_book", "page.weight", variables())
+        .unwrap();
+    assert_eq!(weight.value, Some(10.into()));
+    assert!(matches!(
+        weight.steps.last().unwrap(),
+        Provenance::Transform { formula, part: Some(part), .. }
+            if formula == "page.weight = page.number * .25" && part == "stories/birthday"
+    ));
+}
+
+#[tokio::test]
+async fn it_should_explain_a_replaced_value() {
+    let reader = PartIMReader::from_strings(HashMap::from([
+        (
+            "book".to_string(),
+            "colors: [red]\nextra: !inc::extra".to_string(),
+        ),
+        ("extra".to_string(), "colors: !mix [blue]".to_string()),
+    ]))
+    .unwrap();
+    let app = App::builder(PathBuf::from(env!("CARGO_MANIFEST_DIR")))
+        .with_part_reader(Arc::new(reader))
+        .with_options(AssembleOptions::default().with_merge(MergePolicy::Replace))
+        .build();
+
+    let explanation = app.explain("book", "colors.0", None).unwrap();
+
+    assert_eq!(explanation.value, Some("blue".into()));
+    assert_eq!(
+        explanation.steps.last(),
+        Some(&Provenance::Mixin {
+            part: "extra".to_string(),
+            key: "colors".to_string()
+        })
+    );
+}
+
+#[tokio::test]
+async fn it_should_not_take_dollar_text_for_variables() {
+    let reader = PartIMReader::from_strings(HashMap::from([(
+        "book".to_string(),
+        "price: paid in $USD\ntitle: $TITLE".to_string(),
+    )]))
+    .unwrap();
+    let app = App::builder(PathBuf::from(env!("CARGO_MANIFEST_DIR")))
+        .with_part_reader(Arc::new(reader))
+        .build();
+    let variables = HashMap::from([("TITLE".to_string(), "Cake".to_string())]);
+
+    let price = app
+        .explain("book", "price", Some(variables.clone()))
+        .unwrap();
+    assert_eq!(
+        price.steps,
+        vec![Provenance::Entry {
+            part: "book".to_string()
+        }]
+    );
+
+    let title = app.explain("book", "title", Some(variables)).unwrap();
+    assert_eq!(
+        title.steps.last(),
+        Some(&Provenance::Variable {
+            part: "book".to_string(),
+            expression: "$TITLE".to_string()
+        })
+    );
+}
+
+#[test]
+#[serial]
+fn it_should_explain_from_cli() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/yml_test_files");
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.arg("explain").arg("covers.2.color");
+    cmd.arg("-r").arg(&root);
+    cmd.arg("-e").arg("simple_book");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("covers.2.color = \"black\""))
+        .stdout(predicate::str::contains(
+            "3. mixed into covers with !mix from tags/horror",
+        ));
+}
@@ -0,0 +1,126 @@
+use serde_yaml::Value;
+use std::collections::HashMap;
+use yml_assembler::{adapters::AssemblyOutputFormat, utils::result::AppError};
+
+pub mod test_infra;
+
+static TEST_FILE: &str = "glob_include";
+
+#[tokio::test]
+async fn it_should_include_every_matching_part() {
+    let (app, assembly_output, _) = test_infra::get_test_app();
+    app.compile_and_validate_yml(TEST_FILE, None, None, &AssemblyOutputFormat::Yml)
+        .unwrap();
+    let yml = assembly_output
+        .get_yml_output()
+        .unwrap()
+        .get(TEST_FILE)
+        .unwrap()
+        .clone();
+
+    let expected_yml: Value = serde_yaml::from_str(
+        r#"
+        list:
+          - name: one
+            value: 11
+          - name: three
+            value: 13
+          - name: two
+            value: 12
+        by_name:
+          three:
+            name: three
+            value: 23
+          two:
+            name: two
+            value: 22
+        names:
+          - one
+          - three
+          - two
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(yml, expected_yml);
+}
+
+#[tokio::test]
+async fn it_should_not_include_the_part_matching_its_own_glob() {
+    let (app, assembly_output, _) = test_infra::get_test_app();
+    app.compile_and_validate_yml("cycles/glob/index", None, None, &AssemblyOutputFormat::Yml)
+        .unwrap();
+    let yml = assembly_output
+        .get_yml_output()
+        .unwrap()
+        .get("cycles/glob/index")
+        .unwrap()
+        .clone();
+
+    let expected_yml: Value = serde_yaml::from_str(
+        r#"
+        all:
+          - name: item
+        "#,
+    )
+    .unwrap();
+    assert_eq!(yml, expected_yml);
+}
+
+#[tokio::test]
+async fn it_should_fail_on_include_cycles() {
+    let (app, _, _) = test_infra::get_test_app();
+    let result =
+        app.compile_and_validate_yml("cycles/loop/a", None, None, &AssemblyOutputFormat::Yml);
+
+    assert!(matches!(
+        result,
+        Err(AppError::IncludeCycle(cycle))
+            if cycle == "cycles/loop/a -> cycles/loop/b -> cycles/loop/a"
+    ));
+}
+
+#[tokio::test]
+async fn it_should_include_a_part_in_itself_while_its_variables_change() {
+    let (app, assembly_output, _) = test_infra::get_test_app();
+    let variables = HashMap::from([("level".to_string(), "3".to_string())]);
+    app.compile_and_validate_yml(
+        "cycles/countdown",
+        None,
+        Some(variables),
+        &AssemblyOutputFormat::Yml,
+    )
+    .unwrap();
+    let yml = assembly_output
+        .get_yml_output()
+        .unwrap()
+        .get("cycles/countdown")
+        .unwrap()
+        .clone();
+
+    let expected_yml: Value = serde_yaml::from_str(
+        r#"
+        level: 3
+        next:
+          level: 2
+          next:
+            level: 1
+        "#,
+    )
+    .unwrap();
+    assert_eq!(yml, expected_yml);
+}
+
+#[tokio::test]
+async fn it_should_stop_includes_nested_too_deep() {
+    let (app, _, _) = test_infra::get_test_app();
+    let variables = HashMap::from([("level".to_string(), "0".to_string())]);
+    let result = app.compile_and_validate_yml(
+        "cycles/endless",
+        None,
+        Some(variables),
+        &AssemblyOutputFormat::Yml,
+    );
+
+    assert!(matches!(result, Err(AppError::IncludeCycle(_))));
+}
@@ -72,6 +72,6 @@ async fn it_should_assemble_glob_pattern_folders() {
     let entry_b_file = fs::read_to_string(entry_b_path).unwrap();
     assert!(predicate::str::contains("- a").eval(&entry_b_file));
     assert!(predicate::str::contains("- b").eval(&entry_b_file));
-    
+
     fs::remove_dir_all(PathBuf::from(output)).unwrap();
 }
@@ -0,0 +1,62 @@
+use serde_yaml::Value;
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use yml_assembler::{
+    adapters::AssemblyOutputFormat,
+    lib_infras::{
+        assembly_in_memory_output::AssemblyIMOutput, assembly_part_in_memory_reader::PartIMReader,
+        schema_fs_reader::SchemaFSReader, schema_in_memory_output::SchemaIMOutput,
+    },
+    App,
+};
+
+#[tokio::test]
+async fn it_should_assemble_parts_held_in_memory() {
+    let reader = PartIMReader::from_strings(HashMap::from([
+        (
+            "book".to_string(),
+            r#"
+            title: $TITLE
+            chapters: !inc::chapters/*
+            notes: !text::notes.md
+            "#
+            .to_string(),
+        ),
+        ("chapters/one".to_string(), "name: One".to_string()),
+        ("chapters/two".to_string(), "name: Two".to_string()),
+    ]))
+    .unwrap();
+    reader.insert_resource("notes.md", "Some notes").unwrap();
+
+    let assembly_output = Arc::new(AssemblyIMOutput::new());
+    let app = App::new(
+        Arc::new(reader),
+        Arc::new(SchemaFSReader::new(PathBuf::from(env!(
+            "CARGO_MANIFEST_DIR"
+        )))),
+        assembly_output.clone(),
+        Arc::new(SchemaIMOutput::new()),
+    );
+
+    let variables = HashMap::from([("TITLE".to_string(), "In memory".to_string())]);
+    app.compile_and_validate_yml("book", None, Some(variables), &AssemblyOutputFormat::Yml)
+        .unwrap();
+    let yml = assembly_output
+        .get_yml_output()
+        .unwrap()
+        .get("book")
+        .unwrap()
+        .clone();
+
+    let expected_yml: Value = serde_yaml::from_str(
+        r#"
+        title: In memory
+        chapters:
+          - name: One
+          - name: Two
+        notes: Some notes
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(yml, expected_yml);
+}
@@ -0,0 +1,82 @@
+use serde_yaml::Value;
+use std::{path::PathBuf, sync::Arc};
+use yml_assembler::{
+    adapters::AssemblyOutputFormat,
+    lib_infras::{
+        assembly_in_memory_output::AssemblyIMOutput, assembly_part_fs_reader::PartFSReader,
+        schema_fs_reader::SchemaFSReader, schema_in_memory_output::SchemaIMOutput,
+    },
+    utils::result::AppError,
+    App,
+};
+
+pub mod test_infra;
+
+static TEST_FILE: &str = "include_alias";
+
+#[tokio::test]
+async fn it_should_include_parts_through_an_alias() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/yml_test_files");
+    let assembly_output = Arc::new(AssemblyIMOutput::new());
+    let app = App::new(
+        Arc::new(
+            PartFSReader::new(root.clone()).with_alias("@shared", root.join("overlay/shared")),
+        ),
+        Arc::new(SchemaFSReader::new(root)),
+        assembly_output.clone(),
+        Arc::new(SchemaIMOutput::new()),
+    );
+
+    app.compile_and_validate_yml(TEST_FILE, None, None, &AssemblyOutputFormat::Yml)
+        .unwrap();
+    let yml = assembly_output
+        .get_yml_output()
+        .unwrap()
+        .get(TEST_FILE)
+        .unwrap()
+        .clone();
+
+    let expected_yml: Value = serde_yaml::from_str(
+        r#"
+        hero:
+          name: Shared hero
+        cast:
+          hero:
+            name: Shared hero
+          villain:
+            name: Shared villain
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(yml, expected_yml);
+}
+
+#[tokio::test]
+async fn it_should_fail_on_unknown_alias() {
+    let (app, _, _) = test_infra::get_test_app();
+    let result = app.compile_and_validate_yml(TEST_FILE, None, None, &AssemblyOutputFormat::Yml);
+
+    assert!(matches!(
+        result,
+        Err(AppError::FileSystem(message)) if message.starts_with("Unknown alias @shared")
+    ));
+}
+
+#[tokio::test]
+async fn it_should_keep_relative_includes_in_their_alias() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/yml_test_files");
+    let app = App::new(
+        Arc::new(
+            PartFSReader::new(root.clone()).with_alias("@shared", root.join("overlay/shared")),
+        ),
+        Arc::new(SchemaFSReader::new(root)),
+        Arc::new(AssemblyIMOutput::new()),
+        Arc::new(SchemaIMOutput::new()),
+    );
+
+    let result =
+        app.compile_and_validate_yml("alias_escape", None, None, &AssemblyOutputFormat::Yml);
+
+    assert!(matches!(result, Err(AppError::ForbiddenPath(_))));
+}
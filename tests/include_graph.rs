@@ -0,0 +1,128 @@
+use assert_cmd::prelude::{CommandCargoExt, OutputAssertExt};
+use serial_test::serial;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::PathBuf,
+    process::Command,
+};
+use yml_assembler::graph::{IncludeEdge, MixinEdge};
+
+pub mod test_infra;
+
+#[tokio::test]
+async fn it_should_collect_includes_and_mixins() {
+    let (app, _, _) = test_infra::get_test_app();
+    let graph = app.graph(&["simple_book".to_string()], None).unwrap();
+
+    assert!(graph.entries.contains("simple_book"));
+    assert!(graph.includes.contains(&IncludeEdge {
+        from: "simple_book".to_string(),
+        to: "stories/birthday".to_string(),
+        variables: BTreeMap::from([
+            ("name".to_string(), "Juliette".into()),
+            ("age".to_string(), 21.into()),
+            ("chap1".to_string(), 2.into()),
+            ("chap2".to_string(), 3.into()),
+        ]),
+    }));
+    assert!(graph.includes.contains(&IncludeEdge {
+        from: "tags/horror".to_string(),
+        to: "tags/adult".to_string(),
+        variables: BTreeMap::new(),
+    }));
+    assert!(graph.mixins.contains(&MixinEdge {
+        part: "tags/horror".to_string(),
+        key: "covers".to_string(),
+    }));
+    assert!(graph.mixins.contains(&MixinEdge {
+        part: "stories/birthday".to_string(),
+        key: "_transform".to_string(),
+    }));
+}
+
+#[tokio::test]
+async fn it_should_collect_includes_whatever_their_condition() {
+    let (app, _, _) = test_infra::get_test_app();
+    let adult_edge = IncludeEdge {
+        from: "edition_book".to_string(),
+        to: "tags/adult".to_string(),
+        variables: BTreeMap::new(),
+    };
+
+    let variables = HashMap::from([
+        ("ADULT".to_string(), "false".to_string()),
+        ("EDITION".to_string(), "children".to_string()),
+    ]);
+    let graph = app
+        .graph(&["edition_book".to_string()], Some(variables))
+        .unwrap();
+    assert!(graph.includes.contains(&adult_edge));
+    assert!(graph.failures.is_empty());
+
+    let graph = app.graph(&["edition_book".to_string()], None).unwrap();
+    assert!(graph.includes.contains(&adult_edge));
+    assert!(graph.failures["edition_book"].contains("$ADULT"));
+}
+
+#[tokio::test]
+async fn it_should_not_evaluate_include_paths() {
+    let (app, _, _) = test_infra::get_test_app();
+    let variables = HashMap::from([("EDITION".to_string(), "children".to_string())]);
+
+    let graph = app
+        .graph(&["graph_paths".to_string()], Some(variables))
+        .unwrap();
+
+    assert!(graph
+        .includes
+        .iter()
+        .any(|edge| edge.from == "graph_paths" && edge.to == "2024/01"));
+    assert!(graph.failures["snippets/picker"].contains("$number"));
+    assert!(!graph.failures.contains_key("graph_paths"));
+}
+
+#[test]
+#[serial]
+fn it_should_write_graph_from_cli() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/yml_test_files");
+    let output = "./tests/yml_test_files/graph_output";
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.arg("graph");
+    cmd.arg("-r").arg(&root);
+    cmd.arg("-e").arg("simple_book");
+    cmd.arg("-o").arg(output);
+    cmd.arg("--to").arg("mermaid");
+
+    cmd.assert().success();
+
+    let graph = fs::read_to_string(PathBuf::from(output).join("graph.mmd")).unwrap();
+    assert!(graph.starts_with("flowchart LR"));
+    assert!(graph.contains("[[\"simple_book\"]]"));
+    assert!(graph.contains("\"tags/adult\""));
+
+    fs::remove_dir_all(PathBuf::from(output)).unwrap();
+}
+
+#[test]
+#[serial]
+fn it_should_list_entries_using_a_part() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/yml_test_files");
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.arg("uses").arg("tags/adult");
+    cmd.arg("-r").arg(&root);
+    cmd.arg("-e").arg("*book");
+
+    let std_output = cmd.assert().success().get_output().clone();
+    let std_output = String::from_utf8_lossy(&std_output.stdout);
+    let users = std_output
+        .split("Entries using tags/adult:\n")
+        .nth(1)
+        .unwrap()
+        .lines()
+        .collect::<Vec<&str>>();
+
+    assert_eq!(users, vec!["edition_book", "simple_book"]);
+}
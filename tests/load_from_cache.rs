@@ -22,6 +22,7 @@ async fn it_should_load_from_cache() {
     cmd.arg("-e").arg(entry);
     cmd.arg("-o").arg(output);
 
+    // Part reads are only logged by the command line, the library keeps them quiet.
     let std_output = cmd
         .assert()
         .success()
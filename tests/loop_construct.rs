@@ -0,0 +1,100 @@
+use serde_yaml::Value;
+use yml_assembler::{adapters::AssemblyOutputFormat, utils::result::AppError};
+
+pub mod test_infra;
+
+static TEST_FILE: &str = "loop_book";
+
+#[tokio::test]
+async fn it_should_generate_sequences_and_mappings() {
+    let (app, assembly_output, _) = test_infra::get_test_app();
+    app.compile_and_validate_yml(TEST_FILE, None, None, &AssemblyOutputFormat::Yml)
+        .unwrap();
+    let yml = assembly_output
+        .get_yml_output()
+        .unwrap()
+        .get(TEST_FILE)
+        .unwrap()
+        .clone();
+
+    let expected_yml: Value = serde_yaml::from_str(
+        r#"
+        book:
+          chapters:
+            - title: Chapter 1
+              number: 1
+            - title: Chapter 2
+              number: 2
+            - title: Chapter 3
+              number: 3
+          characters:
+            Juliette:
+              greeting: Hello Juliette
+              rank: 1
+            Romeo:
+              greeting: Hello Romeo
+              rank: 2
+        chapter_pages:
+          - 10
+          - 20
+          - 30
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(yml, expected_yml);
+}
+
+#[tokio::test]
+async fn it_should_bind_loop_variables_over_outer_ones() {
+    let (app, assembly_output, _) = test_infra::get_test_app();
+    let variables = std::collections::HashMap::from([
+        ("chap".to_string(), "outer".to_string()),
+        ("name".to_string(), "outer".to_string()),
+    ]);
+    app.compile_and_validate_yml(TEST_FILE, None, Some(variables), &AssemblyOutputFormat::Yml)
+        .unwrap();
+    let yml = assembly_output
+        .get_yml_output()
+        .unwrap()
+        .get(TEST_FILE)
+        .unwrap()
+        .clone();
+
+    assert_eq!(
+        yml["book"]["chapters"][1]["title"],
+        Value::from("Chapter 2")
+    );
+    assert_eq!(
+        yml["book"]["characters"]["Romeo"]["greeting"],
+        Value::from("Hello Romeo")
+    );
+}
+
+#[tokio::test]
+async fn it_should_fail_on_loops_without_in() {
+    let (app, _, _) = test_infra::get_test_app();
+    let result = app.compile_and_validate_yml(
+        "loops/invalid/missing_in",
+        None,
+        None,
+        &AssemblyOutputFormat::Yml,
+    );
+
+    assert!(
+        matches!(result, Err(AppError::ParseYml(message)) if message.contains("missing its in"))
+    );
+}
+
+#[tokio::test]
+async fn it_should_fail_on_huge_ranges() {
+    let (app, _, _) = test_infra::get_test_app();
+    let result = app.compile_and_validate_yml(
+        "loops/invalid/huge_range",
+        None,
+        None,
+        &AssemblyOutputFormat::Yml,
+    );
+
+    assert!(matches!(result, Err(AppError::ParseYml(message)) if message.contains("more than")));
+}
@@ -0,0 +1,41 @@
+use serde_yaml::Value;
+use yml_assembler::{adapters::AssemblyOutputFormat, utils::result::AppError};
+
+pub mod test_infra;
+
+#[tokio::test]
+async fn it_should_drop_missing_optional_includes() {
+    let test_file = "optional_include";
+    let (app, assembly_output, _) = test_infra::get_test_app();
+    app.compile_and_validate_yml(test_file, None, None, &AssemblyOutputFormat::Yml)
+        .unwrap();
+    let yml = assembly_output
+        .get_yml_output()
+        .unwrap()
+        .get(test_file)
+        .unwrap()
+        .clone();
+
+    let expected_yml: Value = serde_yaml::from_str(
+        r#"
+        title: Standard edition
+        present:
+          title: Special edition
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(yml, expected_yml);
+}
+
+#[tokio::test]
+async fn it_should_still_fail_on_missing_parts_of_an_optional_include() {
+    let test_file = "optional_include_broken";
+    let (app, _, _) = test_infra::get_test_app();
+    let result = app.compile_and_validate_yml(test_file, None, None, &AssemblyOutputFormat::Yml);
+
+    assert!(matches!(
+        result,
+        Err(AppError::MissingPart(part)) if part == "overrides/nowhere"
+    ));
+}
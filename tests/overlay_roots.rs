@@ -0,0 +1,79 @@
+use assert_cmd::prelude::{CommandCargoExt, OutputAssertExt};
+use serde_yaml::Value;
+use serial_test::serial;
+use std::{fs, path::PathBuf, process::Command, sync::Arc};
+use yml_assembler::{
+    adapters::AssemblyOutputFormat,
+    lib_infras::{
+        assembly_in_memory_output::AssemblyIMOutput, assembly_part_fs_reader::PartFSReader,
+        schema_fs_reader::SchemaFSReader, schema_in_memory_output::SchemaIMOutput,
+    },
+    App,
+};
+
+static TEST_FILE: &str = "book";
+
+fn get_roots() -> Vec<PathBuf> {
+    let overlay = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/yml_test_files/overlay");
+    vec![overlay.join("project"), overlay.join("shared")]
+}
+
+#[tokio::test]
+async fn it_should_prefer_parts_from_the_first_root() {
+    let assembly_output = Arc::new(AssemblyIMOutput::new());
+    let app = App::new(
+        Arc::new(PartFSReader::with_roots(get_roots())),
+        Arc::new(SchemaFSReader::with_roots(get_roots())),
+        assembly_output.clone(),
+        Arc::new(SchemaIMOutput::new()),
+    );
+
+    app.compile_and_validate_yml(
+        TEST_FILE,
+        Some("book-schema.yml"),
+        None,
+        &AssemblyOutputFormat::Yml,
+    )
+    .unwrap();
+    let yml = assembly_output
+        .get_yml_output()
+        .unwrap()
+        .get(TEST_FILE)
+        .unwrap()
+        .clone();
+
+    let expected_yml: Value = serde_yaml::from_str(
+        r#"
+        title: Overlay book
+        cast:
+          hero:
+            name: Project hero
+          villain:
+            name: Shared villain
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(yml, expected_yml);
+}
+
+#[test]
+#[serial]
+fn it_should_accept_several_roots_from_cli() {
+    let output = "./tests/yml_test_files/overlay_output";
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    for root in get_roots() {
+        cmd.arg("-r").arg(root);
+    }
+    cmd.arg("-e").arg(TEST_FILE);
+    cmd.arg("-s").arg("book-schema.yml");
+    cmd.arg("-o").arg(output);
+
+    cmd.assert().success();
+
+    let assembled_file = fs::read_to_string(PathBuf::from(output).join("book.yml")).unwrap();
+    assert!(assembled_file.contains("name: Project hero"));
+    assert!(assembled_file.contains("name: Shared villain"));
+
+    fs::remove_dir_all(PathBuf::from(output)).unwrap();
+}
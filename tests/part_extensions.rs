@@ -0,0 +1,82 @@
+use assert_cmd::prelude::{CommandCargoExt, OutputAssertExt};
+use serde_yaml::Value;
+use serial_test::serial;
+use std::{fs, path::PathBuf, process::Command, sync::Arc};
+use yml_assembler::{
+    adapters::AssemblyOutputFormat,
+    lib_infras::{
+        assembly_in_memory_output::AssemblyIMOutput, assembly_part_fs_reader::PartFSReader,
+        schema_fs_reader::SchemaFSReader, schema_in_memory_output::SchemaIMOutput,
+    },
+    App,
+};
+
+static TEST_FILE: &str = "formats/entry";
+
+fn get_extensions() -> Vec<String> {
+    vec!["pyml".to_string(), "yml".to_string(), ".json".to_string()]
+}
+
+#[tokio::test]
+async fn it_should_read_parts_with_configured_extensions() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/yml_test_files");
+    let assembly_output = Arc::new(AssemblyIMOutput::new());
+    let app = App::new(
+        Arc::new(PartFSReader::new(root.clone()).with_extensions(get_extensions())),
+        Arc::new(SchemaFSReader::new(root)),
+        assembly_output.clone(),
+        Arc::new(SchemaIMOutput::new()),
+    );
+
+    app.compile_and_validate_yml(TEST_FILE, None, None, &AssemblyOutputFormat::Yml)
+        .unwrap();
+    let yml = assembly_output
+        .get_yml_output()
+        .unwrap()
+        .get(TEST_FILE)
+        .unwrap()
+        .clone();
+
+    let expected_yml: Value = serde_yaml::from_str(
+        r#"
+        hero:
+          name: Juliette
+        stats:
+          strength: 3
+          skills:
+            - baking
+            - running
+        both:
+          from: pyml
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(yml, expected_yml);
+}
+
+#[test]
+#[serial]
+fn it_should_match_entries_with_configured_extensions_from_cli() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/yml_test_files");
+    let output = "./tests/yml_test_files/formats_output";
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.arg("-r").arg(&root);
+    cmd.arg("-e").arg("formats/*");
+    cmd.arg("-o").arg(output);
+    for extension in get_extensions() {
+        cmd.arg("--extension").arg(extension);
+    }
+
+    cmd.assert().success();
+
+    for file in ["entry", "hero", "stats", "both"] {
+        let path = PathBuf::from(output).join(format!("formats/{file}.yml"));
+        assert!(path.is_file(), "{} was not assembled", path.display());
+    }
+    let both = fs::read_to_string(PathBuf::from(output).join("formats/both.yml")).unwrap();
+    assert!(both.contains("from: pyml"));
+
+    fs::remove_dir_all(PathBuf::from(output)).unwrap();
+}
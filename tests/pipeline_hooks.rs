@@ -0,0 +1,185 @@
+use serde_yaml::Value;
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
+use yml_assembler::{
+    adapters::{AssemblyOutputFormat, PipelineStagePort, PipelineStep, StageContext},
+    lib_infras::{assembly_in_memory_output::AssemblyIMOutput, build_cache_fs::BuildCacheFS},
+    utils::result::AppResult,
+    App,
+};
+
+/// Sorts the tags once mixed and stamps the entry before output.
+struct Stamp;
+impl PipelineStagePort for Stamp {
+    fn id(&self) -> String {
+        "stamp".to_string()
+    }
+
+    fn after(&self, context: &StageContext, mut value: Value) -> AppResult<Value> {
+        if context.step == PipelineStep::Mixins {
+            if let Some(Value::Sequence(tags)) = value.get_mut("tags") {
+                tags.sort_by_key(|tag| tag.as_str().map(|tag| tag.to_string()));
+            }
+        }
+        Ok(value)
+    }
+
+    fn before(&self, context: &StageContext, mut value: Value) -> AppResult<Value> {
+        if context.step == PipelineStep::Output {
+            value["built_from"] = Value::from(context.entry);
+        }
+        Ok(value)
+    }
+}
+
+/// Records the steps it ran around.
+#[derive(Default)]
+struct Steps(Mutex<Vec<String>>);
+impl PipelineStagePort for Steps {
+    fn id(&self) -> String {
+        "steps".to_string()
+    }
+
+    fn before(&self, context: &StageContext, value: Value) -> AppResult<Value> {
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("before {:?}", context.step));
+        Ok(value)
+    }
+
+    fn after(&self, context: &StageContext, value: Value) -> AppResult<Value> {
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("after {:?}", context.step));
+        Ok(value)
+    }
+}
+
+#[tokio::test]
+async fn it_should_run_stages_around_each_step() {
+    let assembly_output = Arc::new(AssemblyIMOutput::new());
+    let steps = Arc::new(Steps::default());
+    let app = App::builder(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/yml_test_files"))
+        .with_assembly_output(assembly_output.clone())
+        .with_stage(Arc::new(Stamp))
+        .with_stage(steps.clone())
+        .build();
+
+    let variables = HashMap::from([
+        ("META".to_string(), "I'm a root variable".to_string()),
+        ("META2".to_string(), "I'm another variable".to_string()),
+    ]);
+    app.compile_and_validate_yml(
+        "simple_book",
+        None,
+        Some(variables),
+        &AssemblyOutputFormat::Yml,
+    )
+    .unwrap();
+
+    let yml = assembly_output.get_yml_output().unwrap();
+    let yml = yml.get("simple_book").unwrap();
+    let tags: Value = serde_yaml::from_str("[adult, horror, ivestigation]").unwrap();
+    assert_eq!(yml["tags"], tags);
+    assert_eq!(yml["built_from"], Value::from("simple_book"));
+
+    assert_eq!(
+        *steps.0.lock().unwrap(),
+        vec![
+            "before Aggregate",
+            "after Aggregate",
+            "before Mixins",
+            "after Mixins",
+            "before Transform",
+            "after Transform",
+            "before Validate",
+            "after Validate",
+            "before Output",
+            "after Output",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn it_should_give_stages_the_entry_as_read() {
+    /// Renames the entry title before its parts are included.
+    struct Retitle;
+    impl PipelineStagePort for Retitle {
+        fn id(&self) -> String {
+            "retitle".to_string()
+        }
+
+        fn before(&self, context: &StageContext, mut value: Value) -> AppResult<Value> {
+            if context.step == PipelineStep::Aggregate {
+                assert!(value.get("title").is_some());
+                value["title"] = Value::from("Retitled $META");
+            }
+            Ok(value)
+        }
+    }
+
+    let assembly_output = Arc::new(AssemblyIMOutput::new());
+    let app = App::builder(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/yml_test_files"))
+        .with_assembly_output(assembly_output.clone())
+        .with_stage(Arc::new(Retitle))
+        .build();
+
+    let variables = HashMap::from([
+        ("META".to_string(), "book".to_string()),
+        ("META2".to_string(), "I'm another variable".to_string()),
+    ]);
+    app.compile_and_validate_yml(
+        "simple_book",
+        None,
+        Some(variables),
+        &AssemblyOutputFormat::Yml,
+    )
+    .unwrap();
+
+    let yml = assembly_output.get_yml_output().unwrap();
+    assert_eq!(yml["simple_book"]["title"], Value::from("Retitled book"));
+}
+
+#[tokio::test]
+async fn it_should_not_reuse_builds_made_with_other_stages() {
+    let cache_dir =
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/yml_test_files/pipeline_hooks_cache");
+    let assembly_output = Arc::new(AssemblyIMOutput::new());
+    let app = |stages: Vec<Arc<dyn PipelineStagePort>>| {
+        let builder =
+            App::builder(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/yml_test_files"))
+                .with_assembly_output(assembly_output.clone())
+                .with_build_cache(Arc::new(BuildCacheFS::new(cache_dir.clone())));
+        stages
+            .into_iter()
+            .fold(builder, |builder, stage| builder.with_stage(stage))
+            .build()
+    };
+    let variables = HashMap::from([
+        ("META".to_string(), "I'm a root variable".to_string()),
+        ("META2".to_string(), "I'm another variable".to_string()),
+    ]);
+    let assemble = |app: App| {
+        app.compile_and_validate_yml(
+            "simple_book",
+            None,
+            Some(variables.clone()),
+            &AssemblyOutputFormat::Yml,
+        )
+        .unwrap();
+        assembly_output.get_yml_output().unwrap()["simple_book"].clone()
+    };
+
+    assert!(assemble(app(vec![])).get("built_from").is_none());
+    assert_eq!(
+        assemble(app(vec![Arc::new(Stamp)]))["built_from"],
+        Value::from("simple_book")
+    );
+
+    std::fs::remove_dir_all(cache_dir).unwrap();
+}
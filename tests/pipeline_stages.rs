@@ -0,0 +1,71 @@
+use serde_yaml::Value;
+use std::{collections::HashMap, sync::Arc};
+use yml_assembler::{
+    aggregator::YmlAggregator, lib_infras::assembly_part_in_memory_reader::PartIMReader,
+    mixins::MixIns, options::MergePolicy, transformable::TransformableList, variables::Variables,
+};
+
+#[test]
+fn it_should_run_each_stage_on_its_own() {
+    let variables: Variables = HashMap::from([("NAME".to_string(), "Juliette".to_string())]).into();
+    let part: Value = serde_yaml::from_str(
+        r#"
+        name: $NAME
+        page:
+          number: 40
+        tags: !mix
+          - horror
+        _transform:
+          - page.weight = page.number * .25
+        "#,
+    )
+    .unwrap();
+
+    let part = variables.inject(&part).unwrap();
+    assert_eq!(part["name"], Value::from("Juliette"));
+
+    let mut mixins = MixIns::new();
+    let part = mixins.trim(&part).unwrap();
+    assert!(part.get("tags").is_none());
+    let part = mixins.inject_with(&part, MergePolicy::Append).unwrap();
+
+    let mut list = TransformableList::try_from(part).unwrap();
+    list.transform().unwrap();
+    let assembled: Value = list.try_into().unwrap();
+
+    let expected: Value = serde_yaml::from_str(
+        r#"
+        name: Juliette
+        page:
+          number: 40
+          weight: 10
+        tags:
+          - horror
+        "#,
+    )
+    .unwrap();
+    assert_eq!(assembled, expected);
+}
+
+#[test]
+fn it_should_aggregate_parts_without_the_later_stages() {
+    let reader = PartIMReader::from_strings(HashMap::from([
+        (
+            "book".to_string(),
+            "story: !inc::story\ntags: !mix [horror]".to_string(),
+        ),
+        ("story".to_string(), "name: $name".to_string()),
+    ]))
+    .unwrap();
+
+    let mut aggregator = YmlAggregator::new(Arc::new(reader));
+    let variables: Variables = HashMap::from([("name".to_string(), "Juliette".to_string())]).into();
+    let yml = aggregator.load("book", &variables).unwrap();
+
+    let expected: Value = serde_yaml::from_str("story:\n  name: Juliette").unwrap();
+    assert_eq!(yml, expected);
+    assert_eq!(
+        aggregator.mixins().get("tags"),
+        Some(&vec![serde_yaml::from_str::<Value>("[horror]").unwrap()])
+    );
+}
@@ -0,0 +1,37 @@
+use serde_yaml::Value;
+use yml_assembler::adapters::AssemblyOutputFormat;
+
+pub mod test_infra;
+
+static TEST_FILE: &str = "relative_include";
+
+#[tokio::test]
+async fn it_should_include_parts_relative_to_including_part() {
+    let (app, assembly_output, _) = test_infra::get_test_app();
+    app.compile_and_validate_yml(TEST_FILE, None, None, &AssemblyOutputFormat::Yml)
+        .unwrap();
+    let yml = assembly_output
+        .get_yml_output()
+        .unwrap()
+        .get(TEST_FILE)
+        .unwrap()
+        .clone();
+
+    let expected_yml: Value = serde_yaml::from_str(
+        r#"
+        story:
+          sibling: Sibling content
+          hero:
+            name: Juliette
+            age: 21
+          deeper:
+            parent_sibling: From deeper
+        covers:
+          - color: blue
+            size: 3
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(yml, expected_yml);
+}
@@ -0,0 +1,85 @@
+use serde_yaml::Value;
+use std::sync::Arc;
+use yml_assembler::{
+    adapters::{AssemblyOutputFormat, PartReaderPort},
+    lib_infras::{
+        assembly_in_memory_output::AssemblyIMOutput, schema_fs_reader::SchemaFSReader,
+        schema_in_memory_output::SchemaIMOutput,
+    },
+    utils::result::{AppError, AppResult},
+    App,
+};
+
+pub mod test_infra;
+
+static TEST_FILE: &str = "resources";
+
+#[tokio::test]
+async fn it_should_include_text_json_yaml_and_csv_files() {
+    let (app, assembly_output, _) = test_infra::get_test_app();
+    app.compile_and_validate_yml(TEST_FILE, None, None, &AssemblyOutputFormat::Yml)
+        .unwrap();
+    let yml = assembly_output
+        .get_yml_output()
+        .unwrap()
+        .get(TEST_FILE)
+        .unwrap()
+        .clone();
+
+    let expected_yml: Value = serde_yaml::from_str(
+        r#"
+        intro: |
+          # Intro
+          Once upon a time.
+        prices:
+          sword: 12
+          shield: 8.5
+          tags:
+            - steel
+            - wood
+        config:
+          difficulty: hard
+          lives: 3
+        characters:
+          - name: Ada
+            level: 3
+            ratio: 0.5
+            alive: true
+          - name: Bob
+            level: 7
+            ratio: 1.25
+            alive: false
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(yml, expected_yml);
+}
+
+/// A reader written before resources existed, it only knows parts.
+struct PartsOnlyReader;
+impl PartReaderPort for PartsOnlyReader {
+    fn get_value(&self, _: &str) -> AppResult<Value> {
+        Ok(serde_yaml::from_str("intro: !text::resources/intro.md").unwrap())
+    }
+
+    fn get_filepathes_from_glob(&self, _: &str) -> AppResult<Vec<String>> {
+        Ok(vec![])
+    }
+}
+
+#[tokio::test]
+async fn it_should_refuse_resources_by_default() {
+    let app = App::new(
+        Arc::new(PartsOnlyReader),
+        Arc::new(SchemaFSReader::new(std::env::temp_dir())),
+        Arc::new(AssemblyIMOutput::new()),
+        Arc::new(SchemaIMOutput::new()),
+    );
+
+    assert!(matches!(
+        app.compile_and_validate_yml("book", None, None, &AssemblyOutputFormat::Yml),
+        Err(AppError::FileSystem(message))
+            if message == "resources/intro.md: resources are not supported by this reader"
+    ));
+}
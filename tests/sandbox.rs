@@ -0,0 +1,59 @@
+use std::{path::PathBuf, sync::Arc};
+use yml_assembler::{
+    adapters::AssemblyOutputFormat,
+    lib_infras::{
+        assembly_in_memory_output::AssemblyIMOutput, assembly_part_fs_reader::PartFSReader,
+        schema_fs_reader::SchemaFSReader, schema_in_memory_output::SchemaIMOutput,
+    },
+    utils::result::AppError,
+    App,
+};
+
+pub mod test_infra;
+
+static TEST_FILE: &str = "sandbox_escape";
+
+#[tokio::test]
+async fn it_should_refuse_files_outside_of_root() {
+    let (app, _, _) = test_infra::get_test_app();
+    let result = app.compile_and_validate_yml(TEST_FILE, None, None, &AssemblyOutputFormat::Yml);
+
+    assert!(matches!(result, Err(AppError::ForbiddenPath(_))));
+}
+
+#[tokio::test]
+async fn it_should_refuse_schemas_outside_of_root() {
+    let (app, _, _) = test_infra::get_test_app();
+    let result = app.compile_and_validate_yml(
+        "simple_book",
+        Some("/etc/schema.yml"),
+        None,
+        &AssemblyOutputFormat::Yml,
+    );
+
+    assert!(matches!(result, Err(AppError::ForbiddenPath(_))));
+}
+
+#[tokio::test]
+async fn it_should_read_outside_of_root_when_allowed() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/yml_test_files");
+    let assembly_output = Arc::new(AssemblyIMOutput::new());
+    let app = App::new(
+        Arc::new(PartFSReader::new(root.clone()).allow_outside_roots()),
+        Arc::new(SchemaFSReader::new(root).allow_outside_roots()),
+        assembly_output.clone(),
+        Arc::new(SchemaIMOutput::new()),
+    );
+
+    app.compile_and_validate_yml(TEST_FILE, None, None, &AssemblyOutputFormat::Yml)
+        .unwrap();
+    let yml = assembly_output
+        .get_yml_output()
+        .unwrap()
+        .get(TEST_FILE)
+        .unwrap()
+        .clone();
+
+    let manifest = yml.get("manifest").unwrap().as_str().unwrap();
+    assert!(manifest.contains("name = \"yml_assembler\""));
+}
@@ -0,0 +1,64 @@
+use serde_yaml::Value;
+use yml_assembler::adapters::AssemblyOutputFormat;
+
+pub mod test_infra;
+
+static TEST_FILE: &str = "select_subtree";
+
+#[tokio::test]
+async fn it_should_include_selected_nodes_only() {
+    let (app, assembly_output, _) = test_infra::get_test_app();
+    app.compile_and_validate_yml(TEST_FILE, None, None, &AssemblyOutputFormat::Yml)
+        .unwrap();
+    let yml = assembly_output
+        .get_yml_output()
+        .unwrap()
+        .get(TEST_FILE)
+        .unwrap()
+        .clone();
+
+    let expected_yml: Value = serde_yaml::from_str(
+        r#"
+        hero:
+          name: Juliette
+          age: 21
+        sidekick_age: 23
+        best_friend: Mercutio
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(yml, expected_yml);
+}
+
+#[tokio::test]
+async fn it_should_keep_the_mixins_of_a_selected_part() {
+    let (app, assembly_output, _) = test_infra::get_test_app();
+    let entry = "select_mixins";
+    app.compile_and_validate_yml(entry, None, None, &AssemblyOutputFormat::Yml)
+        .unwrap();
+    let yml = assembly_output
+        .get_yml_output()
+        .unwrap()
+        .get(entry)
+        .unwrap()
+        .clone();
+
+    let expected_yml: Value = serde_yaml::from_str(
+        r#"
+        hero:
+          name: Juliette
+        tags:
+          - tragedy
+        juliette:
+          tags:
+            - lover
+        romeo:
+          tags:
+            - banished
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(yml, expected_yml);
+}
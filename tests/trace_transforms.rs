@@ -0,0 +1,90 @@
+use assert_cmd::prelude::{CommandCargoExt, OutputAssertExt};
+use predicates::prelude::predicate;
+use predicates::Predicate;
+use serde_yaml::Value;
+use serial_test::serial;
+use std::{fs, path::PathBuf, process::Command, sync::Arc};
+use yml_assembler::{
+    adapters::AssemblyOutputFormat, lib_infras::trace_in_memory_output::TraceIMOutput,
+};
+
+pub mod test_infra;
+
+static TEST_FILE: &str = "simple_book";
+
+#[tokio::test]
+async fn it_should_trace_transforms_with_their_part() {
+    let (app, _, _) = test_infra::get_test_app();
+    let trace_output = Arc::new(TraceIMOutput::new());
+    let app = app.with_trace_output(trace_output.clone());
+
+    app.compile_and_validate_yml(TEST_FILE, None, None, &AssemblyOutputFormat::Yml)
+        .unwrap();
+
+    let traces = trace_output.get_output().unwrap();
+    let traces = traces.get(TEST_FILE).unwrap();
+
+    assert_eq!(traces.len(), 1);
+    assert_eq!(traces[0].formula, "page.weight = page.number * .25");
+    assert_eq!(traces[0].key, "page.weight");
+    assert_eq!(traces[0].old_value, None);
+    assert_eq!(traces[0].new_value, Value::from(10));
+    assert_eq!(traces[0].part, Some("stories/birthday".to_string()));
+}
+
+#[tokio::test]
+#[serial]
+async fn it_should_write_traces_only_when_asked() {
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/yml_test_files")
+        .to_str()
+        .unwrap()
+        .to_string();
+    let output = "./tests/yml_test_files/trace_output";
+    let trace_path = PathBuf::from(output).join("labeled_transform.trace.json");
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.arg("-r").arg(&root);
+    cmd.arg("-e").arg("labeled_transform");
+    cmd.arg("-o").arg(output);
+    cmd.arg("-v").arg("T_LAYER=t30");
+
+    let std_output = cmd.assert().success().get_output().clone();
+    let stdout = String::from_utf8_lossy(&std_output.stdout);
+    assert!(!predicate::str::contains("Setting").eval(&stdout));
+    assert!(!trace_path.exists());
+
+    cmd.arg("--trace-transforms");
+    cmd.assert().success();
+
+    let traces = fs::read_to_string(&trace_path).unwrap();
+    let traces: Vec<serde_json::Value> = serde_json::from_str(&traces).unwrap();
+    let formulas = traces
+        .iter()
+        .map(|trace| trace["formula"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(formulas, vec!["a = a - 10", "a = a * 2", "a = a - 43"]);
+    assert_eq!(traces[2]["new_value"], serde_json::json!(937));
+    assert_eq!(traces[2]["part"], serde_json::json!("labeled_transform"));
+
+    fs::remove_dir_all(PathBuf::from(output)).unwrap();
+}
+
+#[tokio::test]
+async fn it_should_trace_identical_formulas_to_their_own_part() {
+    let (app, _, _) = test_infra::get_test_app();
+    let trace_output = Arc::new(TraceIMOutput::new());
+    let app = app.with_trace_output(trace_output.clone());
+
+    app.compile_and_validate_yml("counted/book", None, None, &AssemblyOutputFormat::Yml)
+        .unwrap();
+
+    let traces = trace_output.get_output().unwrap();
+    let parts = traces
+        .get("counted/book")
+        .unwrap()
+        .iter()
+        .map(|trace| trace.part.clone().unwrap())
+        .collect::<Vec<String>>();
+    assert_eq!(parts, vec!["counted/book", "counted/extra"]);
+}
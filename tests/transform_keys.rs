@@ -0,0 +1,38 @@
+use serde_yaml::Value;
+use yml_assembler::adapters::AssemblyOutputFormat;
+
+pub mod test_infra;
+
+static TEST_FILE: &str = "transform_keys";
+
+#[tokio::test]
+async fn it_should_create_and_delete_keys() {
+    let (app, assembly_output, _) = test_infra::get_test_app();
+    app.compile_and_validate_yml(TEST_FILE, None, None, &AssemblyOutputFormat::Yml)
+        .unwrap();
+    let yml = assembly_output
+        .get_yml_output()
+        .unwrap()
+        .get(TEST_FILE)
+        .unwrap()
+        .clone();
+
+    let expected_yml: Value = serde_yaml::from_str(
+        r#"
+        title: Some book
+        chapters:
+          - one
+          - three
+          - four
+        authors:
+          - Jane
+          - John
+        meta:
+          pages:
+            count: 3
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(yml, expected_yml);
+}
@@ -0,0 +1,59 @@
+use assert_cmd::prelude::CommandCargoExt;
+use serial_test::serial;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    thread::sleep,
+    time::Duration,
+};
+
+static WATCH_DIR: &str = "./tests/yml_test_files/watch_output";
+
+fn wait_for(path: &Path, content: &str) -> bool {
+    (0..100).any(|_| {
+        sleep(Duration::from_millis(100));
+        fs::read_to_string(path).is_ok_and(|file| file.contains(content))
+    })
+}
+
+#[test]
+#[serial]
+fn it_should_assemble_again_entries_affected_by_a_change() {
+    let root = PathBuf::from(WATCH_DIR).join("root");
+    let output = PathBuf::from(WATCH_DIR).join("output");
+    fs::create_dir_all(&root).unwrap();
+    fs::write(root.join("book_a.pyml"), "hero: !inc::hero").unwrap();
+    fs::write(root.join("book_b.pyml"), "villain: !inc::villain").unwrap();
+    fs::write(root.join("hero.pyml"), "name: Juliette").unwrap();
+    fs::write(root.join("villain.pyml"), "name: Gateau").unwrap();
+
+    let mut cmd = Command::cargo_bin(env!("CARGO_PKG_NAME")).unwrap();
+    cmd.arg("watch");
+    cmd.arg("-r").arg(&root);
+    cmd.arg("-e").arg("book_*");
+    cmd.arg("-o").arg(&output);
+    cmd.arg("--interval").arg("50");
+    let mut child = cmd.stdout(Stdio::piped()).spawn().unwrap();
+
+    let book_a = output.join("book_a.yml");
+    let book_b = output.join("book_b.yml");
+    assert!(wait_for(&book_a, "Juliette"));
+    assert!(wait_for(&book_b, "Gateau"));
+    fs::remove_file(&book_b).unwrap();
+
+    fs::write(root.join("hero.pyml"), "name: Romeo").unwrap();
+    let rebuilt = wait_for(&book_a, "Romeo");
+    fs::write(root.join("book_c.pyml"), "hero: !inc::hero").unwrap();
+    let created = wait_for(&output.join("book_c.yml"), "Romeo");
+
+    child.kill().unwrap();
+    let std_output = child.wait_with_output().unwrap();
+    println!("{}", String::from_utf8_lossy(&std_output.stdout));
+
+    assert!(rebuilt);
+    assert!(created);
+    assert!(!book_b.exists());
+
+    fs::remove_dir_all(WATCH_DIR).unwrap();
+}